@@ -0,0 +1,52 @@
+use clap::{CommandFactory, Parser};
+
+use crate::{
+    program_context::ProgramContext,
+    query::time_travel::{SnapshotRef, SnapshotSource, deregister_snapshots, register_snapshots},
+    runner::run_sql,
+    utils::args::parse_args,
+};
+
+const CHANGES: &str = "__deltaq_changes";
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "changes",
+    about = "Display the Change Data Feed of a table between two versions"
+)]
+struct ChangesArgs {
+    /// Table name
+    table_name: String,
+
+    /// First version to read changes from
+    #[arg(long)]
+    from: i64,
+
+    /// Last version to read changes from [default: latest]
+    #[arg(long)]
+    to: Option<i64>,
+}
+
+pub fn command() -> clap::Command {
+    ChangesArgs::command()
+}
+
+pub async fn changes_command(ctx: &ProgramContext, line: &str) -> Result<(), String> {
+    let Some(args) = parse_args::<ChangesArgs>(line)? else {
+        return Ok(());
+    };
+
+    let snapshots = [SnapshotRef {
+        temp_name: CHANGES.into(),
+        table_name: args.table_name,
+        source: SnapshotSource::Changes(args.from, args.to),
+    }];
+    let registered = register_snapshots(ctx, &snapshots).await?;
+    let result = run_sql(
+        ctx,
+        &format!("SELECT * FROM {} ORDER BY _commit_version", CHANGES),
+    )
+    .await;
+    deregister_snapshots(ctx, &registered);
+    result
+}
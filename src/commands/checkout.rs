@@ -0,0 +1,39 @@
+use clap::{CommandFactory, Parser};
+
+use crate::{
+    program_context::ProgramContext,
+    utils::{args::parse_args, time_travel::TableVersion},
+};
+
+#[derive(Parser, Debug)]
+#[command(name = "checkout", about = "Move an opened table to another version")]
+struct CheckoutArgs {
+    /// Table name
+    table_name: String,
+
+    /// A version number, an RFC 3339 timestamp or YYYY-MM-DD, or "latest"
+    #[arg(value_parser = TableVersion::parse)]
+    version: TableVersion,
+}
+
+pub fn command() -> clap::Command {
+    CheckoutArgs::command()
+}
+
+pub async fn checkout_command(ctx: &mut ProgramContext, line: &str) -> Result<(), String> {
+    let Some(args) = parse_args::<CheckoutArgs>(line)? else {
+        return Ok(());
+    };
+
+    let table = ctx.load_snapshot(&args.table_name, &args.version).await?;
+    let version = table.version();
+
+    ctx.refresh_table(&args.table_name, table).await;
+    ctx.set_pin(&args.table_name, Some(args.version));
+
+    match version {
+        Some(version) => println!("{} is now at version {}", args.table_name, version),
+        None => println!("{} has no versions", args.table_name),
+    }
+    Ok(())
+}
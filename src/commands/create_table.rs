@@ -1,12 +1,10 @@
-use std::sync::Arc;
-
-use clap::{Parser, arg, command};
+use clap::{CommandFactory, Parser};
 use deltalake::DeltaOps;
 
-use crate::{program_context::ProgramContext, schema};
+use crate::{program_context::ProgramContext, schema, utils::args::parse_args};
 
 #[derive(Parser, Debug)]
-#[command(name = "open", about = "Open a Delta table")]
+#[command(name = "create", about = "Create a Delta table")]
 struct CreateArgs {
     /// Logical name you want to assign
     table_name: String,
@@ -19,34 +17,27 @@ struct CreateArgs {
     schema: String,
 }
 
-pub async fn create_table_command(ctx: &mut ProgramContext, line: &str) {
-    let args = match CreateArgs::try_parse_from(shell_words::split(line).expect("parse failed")) {
-        Ok(args) => args,
-        Err(e) => {
-            e.print().expect("error writing to stderr");
-            return;
-        }
-    };
+pub fn command() -> clap::Command {
+    CreateArgs::command()
+}
 
-    let schema = match schema::parser::parse_schema(args.schema.as_str()) {
-        Ok(schema) => schema,
-        Err(e) => {
-            println!("{}", e);
-            return;
-        }
+pub async fn create_table_command(ctx: &mut ProgramContext, line: &str) -> Result<(), String> {
+    let Some(args) = parse_args::<CreateArgs>(line)? else {
+        return Ok(());
     };
 
-    let table = DeltaOps::try_from_uri(&args.table_name)
+    let schema = schema::parser::parse_schema(args.schema.as_str()).map_err(|e| e.to_string())?;
+
+    let table = DeltaOps::try_from_uri(&args.table_path)
         .await
-        .unwrap()
+        .map_err(|e| e.to_string())?
         .create()
         .with_table_name(&args.table_name)
         .with_columns(schema)
         .await
-        .unwrap();
+        .map_err(|e| e.to_string())?;
 
-    let table = Arc::new(table);
-    ctx.df_ctx.register_table(&args.table_name, table.clone()).unwrap();
-    ctx.tables.insert(args.table_name, table);
-    // TODO remove all unwraps
+    ctx.refresh_table(&args.table_name, table).await;
+    ctx.set_pin(&args.table_name, None);
+    Ok(())
 }
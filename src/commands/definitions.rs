@@ -0,0 +1,26 @@
+use super::{
+    changes, checkout, create_table, diff, diff_files, display_schema, help, history, insert,
+    open_table, profiles, refresh, restore, set, tables, tail,
+};
+
+/// The clap definition of every dot-command, named without the leading dot
+pub fn command_definitions() -> Vec<clap::Command> {
+    vec![
+        open_table::command(),
+        create_table::command(),
+        tables::command(),
+        display_schema::command(),
+        insert::command(),
+        history::command(),
+        checkout::command(),
+        diff::command(),
+        diff_files::command(),
+        changes::command(),
+        tail::command(),
+        restore::command(),
+        refresh::command(),
+        profiles::command(),
+        set::command(),
+        help::command(),
+    ]
+}
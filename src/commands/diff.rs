@@ -0,0 +1,276 @@
+use clap::{CommandFactory, Parser};
+use deltalake::arrow::datatypes::Fields;
+
+use crate::{
+    program_context::ProgramContext,
+    query::time_travel::{SnapshotRef, SnapshotSource, deregister_snapshots, register_snapshots},
+    runner::run_sql,
+    utils::{
+        args::parse_args,
+        sql::{quote_ident, quote_literal},
+        time_travel::TableVersion,
+    },
+};
+
+const OLD: &str = "__deltaq_diff_old";
+const NEW: &str = "__deltaq_diff_new";
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "diff",
+    about = "Display the rows that changed between two versions of a table"
+)]
+struct DiffArgs {
+    /// Table name
+    table_name: String,
+
+    /// Version to compare from: a version number, a timestamp, or "latest"
+    #[arg(value_parser = TableVersion::parse)]
+    from: TableVersion,
+
+    /// Version to compare to: a version number, a timestamp, or "latest"
+    #[arg(value_parser = TableVersion::parse)]
+    to: TableVersion,
+
+    /// Comma separated key columns, used to pair up the rows that changed
+    #[arg(long, value_delimiter = ',')]
+    key: Vec<String>,
+}
+
+pub fn command() -> clap::Command {
+    DiffArgs::command()
+}
+
+pub async fn diff_command(ctx: &ProgramContext, line: &str) -> Result<(), String> {
+    let Some(args) = parse_args::<DiffArgs>(line)? else {
+        return Ok(());
+    };
+
+    let snapshots = [
+        SnapshotRef {
+            temp_name: OLD.into(),
+            table_name: args.table_name.clone(),
+            source: SnapshotSource::Version(args.from),
+        },
+        SnapshotRef {
+            temp_name: NEW.into(),
+            table_name: args.table_name,
+            source: SnapshotSource::Version(args.to),
+        },
+    ];
+    let registered = register_snapshots(ctx, &snapshots).await?;
+    let result = run_diff(ctx, &args.key).await;
+    deregister_snapshots(ctx, &registered);
+    result
+}
+
+async fn run_diff(ctx: &ProgramContext, key: &[String]) -> Result<(), String> {
+    let old = ctx.df_ctx.table(OLD).await.map_err(|e| e.to_string())?;
+    let new = ctx.df_ctx.table(NEW).await.map_err(|e| e.to_string())?;
+    let (columns, notes) = compare_columns(old.schema().fields(), new.schema().fields());
+
+    if let Some(missing) = key.iter().find(|k| !columns.contains(k)) {
+        return Err(format!(
+            "key column '{}' not found in both versions with the same type",
+            missing
+        ));
+    }
+    if !notes.is_empty() {
+        println!("The schema changed, only the columns in both versions are compared:");
+        for note in notes {
+            println!("  {}", note);
+        }
+    }
+
+    let query = if key.is_empty() {
+        diff_without_key(&columns)
+    } else {
+        diff_with_key(&columns, key)
+    };
+    run_sql(ctx, &query).await
+}
+
+/// Returns the columns both versions have with the same type, and a note for every other one
+fn compare_columns(old: &Fields, new: &Fields) -> (Vec<String>, Vec<String>) {
+    let mut columns = Vec::new();
+    let mut notes = Vec::new();
+    for field in new {
+        match old.find(field.name()) {
+            Some((_, before)) if before.data_type() == field.data_type() => {
+                columns.push(field.name().clone())
+            }
+            Some((_, before)) => notes.push(format!(
+                "{}: type changed from {} to {}",
+                field.name(),
+                before.data_type(),
+                field.data_type()
+            )),
+            None => notes.push(format!("{}: added", field.name())),
+        }
+    }
+    for field in old {
+        if new.find(field.name()).is_none() {
+            notes.push(format!("{}: removed", field.name()));
+        }
+    }
+    (columns, notes)
+}
+
+/// The columns as a select list, qualified with `alias` if given
+fn select_list(columns: &[String], alias: Option<&str>) -> String {
+    columns
+        .iter()
+        .map(|c| match alias {
+            Some(alias) => format!("{}.{}", alias, quote_ident(c)),
+            None => quote_ident(c),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Without a key, a changed row shows up as a deleted row and an inserted one
+fn diff_without_key(columns: &[String]) -> String {
+    format!(
+        "SELECT 'inserted' AS _change, * FROM \
+         (SELECT {columns} FROM {new} EXCEPT ALL SELECT {columns} FROM {old}) \
+         UNION ALL \
+         SELECT 'deleted' AS _change, * FROM \
+         (SELECT {columns} FROM {old} EXCEPT ALL SELECT {columns} FROM {new})",
+        columns = select_list(columns, None),
+        old = OLD,
+        new = NEW
+    )
+}
+
+/// With a key, rows with the same key in both versions are paired up, and the columns
+/// that differ are listed in `_changed_columns`
+fn diff_with_key(columns: &[String], key: &[String]) -> String {
+    let on = key
+        .iter()
+        .map(|k| format!("o.{k} = n.{k}", k = quote_ident(k)))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    let values: Vec<&String> = columns.iter().filter(|c| !key.contains(c)).collect();
+
+    let mut parts = vec![
+        format!(
+            "SELECT 'inserted' AS _change, {columns}, CAST(NULL AS VARCHAR) AS _changed_columns \
+             FROM {new} n WHERE NOT EXISTS (SELECT 1 FROM {old} o WHERE {on})",
+            columns = select_list(columns, Some("n")),
+            old = OLD,
+            new = NEW,
+            on = on
+        ),
+        format!(
+            "SELECT 'deleted' AS _change, {columns}, CAST(NULL AS VARCHAR) AS _changed_columns \
+             FROM {old} o WHERE NOT EXISTS (SELECT 1 FROM {new} n WHERE {on})",
+            columns = select_list(columns, Some("o")),
+            old = OLD,
+            new = NEW,
+            on = on
+        ),
+    ];
+
+    if !values.is_empty() {
+        let distinct = |c: &String| format!("o.{c} IS DISTINCT FROM n.{c}", c = quote_ident(c));
+        let changed_columns = values
+            .iter()
+            .map(|c| format!("CASE WHEN {} THEN {} END", distinct(c), quote_literal(c)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let changed = values
+            .iter()
+            .map(|c| distinct(c))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        parts.push(format!(
+            "SELECT 'changed' AS _change, {columns}, concat_ws(', ', {changed_columns}) AS _changed_columns \
+             FROM {old} o JOIN {new} n ON {on} WHERE {changed}",
+            columns = select_list(columns, Some("n")),
+            old = OLD,
+            new = NEW,
+            on = on,
+            changed_columns = changed_columns,
+            changed = changed
+        ));
+    }
+
+    parts.join(" UNION ALL ")
+}
+
+#[cfg(test)]
+mod test {
+    use deltalake::arrow::datatypes::{DataType, Field, Fields};
+
+    use crate::commands::diff::{compare_columns, diff_with_key, diff_without_key};
+
+    fn columns(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_compare_columns() {
+        let old = Fields::from(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("status", DataType::Utf8, true),
+            Field::new("amount", DataType::Int32, true),
+            Field::new("note", DataType::Utf8, true),
+        ]);
+        let new = Fields::from(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("status", DataType::Utf8, true),
+            Field::new("amount", DataType::Int64, true),
+            Field::new("updated_at", DataType::Date32, true),
+        ]);
+        assert_eq!(
+            compare_columns(&old, &new),
+            (
+                columns(&["id", "status"]),
+                columns(&[
+                    "amount: type changed from Int32 to Int64",
+                    "updated_at: added",
+                    "note: removed",
+                ])
+            )
+        );
+        assert_eq!(
+            compare_columns(&old, &old),
+            (columns(&["id", "status", "amount", "note"]), Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_diff_without_key() {
+        assert_eq!(
+            diff_without_key(&columns(&["id", "status"])),
+            "SELECT 'inserted' AS _change, * FROM \
+             (SELECT \"id\", \"status\" FROM __deltaq_diff_new \
+             EXCEPT ALL SELECT \"id\", \"status\" FROM __deltaq_diff_old) \
+             UNION ALL \
+             SELECT 'deleted' AS _change, * FROM \
+             (SELECT \"id\", \"status\" FROM __deltaq_diff_old \
+             EXCEPT ALL SELECT \"id\", \"status\" FROM __deltaq_diff_new)"
+        );
+    }
+
+    #[test]
+    fn test_diff_with_key() {
+        assert_eq!(
+            diff_with_key(&columns(&["id", "status"]), &columns(&["id"])),
+            "SELECT 'inserted' AS _change, n.\"id\", n.\"status\", \
+             CAST(NULL AS VARCHAR) AS _changed_columns FROM __deltaq_diff_new n \
+             WHERE NOT EXISTS (SELECT 1 FROM __deltaq_diff_old o WHERE o.\"id\" = n.\"id\") \
+             UNION ALL \
+             SELECT 'deleted' AS _change, o.\"id\", o.\"status\", \
+             CAST(NULL AS VARCHAR) AS _changed_columns FROM __deltaq_diff_old o \
+             WHERE NOT EXISTS (SELECT 1 FROM __deltaq_diff_new n WHERE o.\"id\" = n.\"id\") \
+             UNION ALL \
+             SELECT 'changed' AS _change, n.\"id\", n.\"status\", \
+             concat_ws(', ', CASE WHEN o.\"status\" IS DISTINCT FROM n.\"status\" THEN 'status' END) \
+             AS _changed_columns FROM __deltaq_diff_old o JOIN __deltaq_diff_new n ON o.\"id\" = n.\"id\" \
+             WHERE o.\"status\" IS DISTINCT FROM n.\"status\""
+        );
+        // Without other columns, there is nothing that can change
+        assert!(!diff_with_key(&columns(&["id"]), &columns(&["id"])).contains("'changed'"));
+    }
+}
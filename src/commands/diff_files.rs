@@ -0,0 +1,291 @@
+use std::{collections::HashMap, sync::Arc};
+
+use clap::{CommandFactory, Parser};
+use deltalake::{
+    DeltaTable,
+    arrow::{
+        array::{ArrayRef, AsArray, Int64Array, RecordBatch, StringArray},
+        compute::cast,
+        datatypes::{DataType, Int64Type},
+        util::pretty::pretty_format_batches,
+    },
+    kernel::Action,
+};
+use serde_json::Value;
+
+use crate::{
+    program_context::ProgramContext,
+    utils::{
+        args::parse_args,
+        delta_log::{decode_path, read_commit},
+        time_travel::TableVersion,
+    },
+};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "diff-files",
+    about = "Display the files added and removed between two versions of a table"
+)]
+struct DiffFilesArgs {
+    /// Table name
+    table_name: String,
+
+    /// Version to compare from: a version number, a timestamp, or "latest"
+    #[arg(value_parser = TableVersion::parse)]
+    from: TableVersion,
+
+    /// Version to compare to: a version number, a timestamp, or "latest"
+    #[arg(value_parser = TableVersion::parse)]
+    to: TableVersion,
+}
+
+pub fn command() -> clap::Command {
+    DiffFilesArgs::command()
+}
+
+/// A file added or removed by a commit
+struct FileChange {
+    added: bool,
+    version: i64,
+    path: String,
+    size: Option<i64>,
+    partition_values: String,
+    records: Option<i64>,
+}
+
+pub async fn diff_files_command(ctx: &ProgramContext, line: &str) -> Result<(), String> {
+    let Some(args) = parse_args::<DiffFilesArgs>(line)? else {
+        return Ok(());
+    };
+
+    let old = ctx.load_snapshot(&args.table_name, &args.from).await?;
+    let new = ctx.load_snapshot(&args.table_name, &args.to).await?;
+    let (Some(from), Some(to)) = (old.version(), new.version()) else {
+        return Err(format!("table '{}' has no versions", args.table_name));
+    };
+    if from > to {
+        return Err(format!(
+            "{} is newer than {}, swap them to compare",
+            args.from, args.to
+        ));
+    }
+
+    let mut changes: Vec<FileChange> = Vec::new();
+    for version in from + 1..=to {
+        for action in read_commit(&new, version).await? {
+            match action {
+                Action::Add(add) => push_change(
+                    &mut changes,
+                    FileChange {
+                        added: true,
+                        version,
+                        path: decode_path(&add.path),
+                        size: Some(add.size),
+                        records: num_records(add.stats.as_deref()),
+                        partition_values: format_partition_values(&add.partition_values),
+                    },
+                ),
+                Action::Remove(remove) => push_change(
+                    &mut changes,
+                    FileChange {
+                        added: false,
+                        version,
+                        path: decode_path(&remove.path),
+                        size: remove.size,
+                        records: None,
+                        partition_values: remove
+                            .partition_values
+                            .as_ref()
+                            .map(format_partition_values)
+                            .unwrap_or_default(),
+                    },
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    // Removes don't carry stats, they are in the adds of the old snapshot
+    if changes.iter().any(|c| !c.added) {
+        let records = records_by_path(&old)?;
+        for change in changes.iter_mut().filter(|c| !c.added) {
+            change.records = records.get(&change.path).copied();
+        }
+    }
+
+    if !changes.is_empty() {
+        let batch = to_record_batch(&changes)?;
+        let table = pretty_format_batches(&[batch]).map_err(|e| e.to_string())?;
+        println!("{}", table);
+    }
+    for added in [true, false] {
+        let files: Vec<&FileChange> = changes.iter().filter(|c| c.added == added).collect();
+        println!(
+            "{} {} files, {} bytes, {} records",
+            if added { "Added" } else { "Removed" },
+            files.len(),
+            files.iter().filter_map(|c| c.size).sum::<i64>(),
+            files.iter().filter_map(|c| c.records).sum::<i64>()
+        );
+    }
+    Ok(())
+}
+
+/// Adds a change, unless it undoes an earlier one of the same file. A file added and removed
+/// again in between never made it to `to`, and one removed and added back, e.g. by a restore,
+/// is still there
+fn push_change(changes: &mut Vec<FileChange>, change: FileChange) {
+    match changes
+        .iter()
+        .position(|c| c.added != change.added && c.path == change.path)
+    {
+        Some(i) => {
+            changes.remove(i);
+        }
+        None => changes.push(change),
+    }
+}
+
+/// Reads `numRecords` from the JSON stats of an add action
+fn num_records(stats: Option<&str>) -> Option<i64> {
+    let stats: Value = serde_json::from_str(stats?).ok()?;
+    stats.get("numRecords")?.as_i64()
+}
+
+/// Formats partition values as `key=value` pairs, sorted by key
+fn format_partition_values<'a>(
+    values: impl IntoIterator<Item = (&'a String, &'a Option<String>)>,
+) -> String {
+    let mut values: Vec<_> = values.into_iter().collect();
+    values.sort_by_key(|(k, _)| *k);
+    values
+        .into_iter()
+        .map(|(k, v)| format!("{}={}", k, v.as_deref().unwrap_or("NULL")))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Number of records of every file in a snapshot, by path
+fn records_by_path(table: &DeltaTable) -> Result<HashMap<String, i64>, String> {
+    let files = table
+        .snapshot()
+        .and_then(|snapshot| snapshot.add_actions_table(true))
+        .map_err(|e| e.to_string())?;
+    let (Some(paths), Some(records)) = (
+        files.column_by_name("path"),
+        files.column_by_name("num_records"),
+    ) else {
+        return Ok(HashMap::new());
+    };
+    let paths = cast(paths, &DataType::Utf8).map_err(|e| e.to_string())?;
+    let records = cast(records, &DataType::Int64).map_err(|e| e.to_string())?;
+
+    Ok(paths
+        .as_string::<i32>()
+        .iter()
+        .zip(records.as_primitive::<Int64Type>().iter())
+        .filter_map(|(path, records)| Some((path?.to_string(), records?)))
+        .collect())
+}
+
+fn to_record_batch(changes: &[FileChange]) -> Result<RecordBatch, String> {
+    let columns: Vec<(&str, ArrayRef)> = vec![
+        (
+            "action",
+            Arc::new(StringArray::from_iter_values(
+                changes
+                    .iter()
+                    .map(|c| if c.added { "add" } else { "remove" }),
+            )),
+        ),
+        (
+            "version",
+            Arc::new(Int64Array::from_iter_values(
+                changes.iter().map(|c| c.version),
+            )),
+        ),
+        (
+            "path",
+            Arc::new(StringArray::from_iter_values(
+                changes.iter().map(|c| c.path.as_str()),
+            )),
+        ),
+        (
+            "size",
+            Arc::new(Int64Array::from_iter(changes.iter().map(|c| c.size))),
+        ),
+        (
+            "partition_values",
+            Arc::new(StringArray::from_iter_values(
+                changes.iter().map(|c| c.partition_values.as_str()),
+            )),
+        ),
+        (
+            "records",
+            Arc::new(Int64Array::from_iter(changes.iter().map(|c| c.records))),
+        ),
+    ];
+    RecordBatch::try_from_iter(columns).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::commands::diff_files::{
+        FileChange, format_partition_values, num_records, push_change,
+    };
+
+    fn change(added: bool, version: i64, path: &str) -> FileChange {
+        FileChange {
+            added,
+            version,
+            path: path.to_string(),
+            size: Some(10),
+            partition_values: String::new(),
+            records: None,
+        }
+    }
+
+    #[test]
+    fn test_num_records() {
+        assert_eq!(
+            num_records(Some(r#"{"numRecords":42,"minValues":{"id":1}}"#)),
+            Some(42)
+        );
+        assert_eq!(num_records(Some(r#"{"minValues":{}}"#)), None);
+        assert_eq!(num_records(Some("not json")), None);
+        assert_eq!(num_records(None), None);
+    }
+
+    #[test]
+    fn test_format_partition_values() {
+        let date = ("date".to_string(), Some("2026-01-01".to_string()));
+        let region = ("region".to_string(), None);
+        assert_eq!(
+            format_partition_values([(&region.0, &region.1), (&date.0, &date.1)]),
+            "date=2026-01-01, region=NULL"
+        );
+    }
+
+    #[test]
+    fn test_push_change() {
+        let mut changes = Vec::new();
+        // Added and removed again
+        push_change(&mut changes, change(true, 1, "a.parquet"));
+        push_change(&mut changes, change(false, 2, "a.parquet"));
+        // Removed and added back
+        push_change(&mut changes, change(false, 2, "b.parquet"));
+        push_change(&mut changes, change(true, 3, "b.parquet"));
+        push_change(&mut changes, change(true, 3, "c.parquet"));
+        push_change(&mut changes, change(false, 3, "d.parquet"));
+
+        let changes: Vec<(bool, i64, &str)> = changes
+            .iter()
+            .map(|c| (c.added, c.version, c.path.as_str()))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![(true, 3, "c.parquet"), (false, 3, "d.parquet")]
+        );
+    }
+}
@@ -1,34 +1,32 @@
 use deltalake::datafusion::prelude::SessionContext;
 
-use clap::Parser;
+use clap::{CommandFactory, Parser};
 
-use crate::utils::data_type::arrow_type_to_delta_str;
+use crate::utils::{args::parse_args, data_type::arrow_type_to_delta_str};
 
 #[derive(Parser, Debug)]
-#[command(name = "open", about = "Open a Delta table")]
+#[command(name = "schema", about = "Display the schema of a table")]
 struct SchemaArgs {
     /// Name for the loaded table
     table_name: String,
 }
 
-pub async fn display_schema_command(ctx: &SessionContext, line: &str) {
-    let args = match SchemaArgs::try_parse_from(shell_words::split(line).expect("parse failed")) {
-        Ok(args) => args,
-        Err(e) => {
-            e.print().expect("error writing to stderr");
-            return;
-        }
-    };
+pub fn command() -> clap::Command {
+    SchemaArgs::command()
+}
 
-    let df = ctx.table(args.table_name).await.unwrap().limit(0, Some(0));
-    let schema = match &df {
-        Ok(df) => df.schema(),
-        Err(e) => { 
-            println!("{}", e);
-            return;
-        },
+pub async fn display_schema_command(ctx: &SessionContext, line: &str) -> Result<(), String> {
+    let Some(args) = parse_args::<SchemaArgs>(line)? else {
+        return Ok(());
     };
 
+    let df = ctx
+        .table(args.table_name)
+        .await
+        .and_then(|df| df.limit(0, Some(0)))
+        .map_err(|e| e.to_string())?;
+    let schema = df.schema();
+
     for f in schema.fields() {
         match arrow_type_to_delta_str(f.data_type()) {
             Ok(t) => println!("  {}: {}", f.name(), t),
@@ -42,5 +40,5 @@ pub async fn display_schema_command(ctx: &SessionContext, line: &str) {
             println!("  {}: {}", k, v);
         }
     }
+    Ok(())
 }
-
@@ -0,0 +1,81 @@
+use clap::{CommandFactory, Parser};
+
+use crate::utils::{args::parse_args, suggest::did_you_mean};
+
+use super::definitions::command_definitions;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "help",
+    about = "List the commands, or show the help for one of them"
+)]
+struct HelpArgs {
+    /// Command to show the help for
+    command: Option<String>,
+}
+
+pub fn command() -> clap::Command {
+    HelpArgs::command()
+}
+
+pub fn help_command(line: &str) -> Result<(), String> {
+    let Some(args) = parse_args::<HelpArgs>(line)? else {
+        return Ok(());
+    };
+
+    let Some(name) = args.command else {
+        print_command_list();
+        return Ok(());
+    };
+
+    let name = name.trim_start_matches('.');
+    match find_command(name) {
+        Some(mut command) => {
+            command.print_help().map_err(|e| e.to_string())?;
+            Ok(())
+        }
+        None => Err(unknown_command_message(&format!(".{}", name))),
+    }
+}
+
+/// Finds a command definition by its name, without the leading dot
+fn find_command(name: &str) -> Option<clap::Command> {
+    command_definitions()
+        .into_iter()
+        .find(|c| c.get_name() == name)
+        .map(|c| {
+            let bin_name = format!(".{}", c.get_name());
+            c.bin_name(bin_name)
+        })
+}
+
+fn print_command_list() {
+    println!("Commands: (type .help <command> for more help about a specific command)");
+    for command in command_definitions() {
+        let bin_name = format!(".{}", command.get_name());
+        let mut command = command.bin_name(bin_name);
+        let usage = command.render_usage().to_string();
+        println!("{}", usage.trim_start_matches("Usage: "));
+        if let Some(about) = command.get_about() {
+            println!("    {}", about);
+        }
+    }
+    println!("Anything not starting with a dot is run as SQL, until a line ending in ';'.");
+    println!("Type quit, exit or \\q to leave.");
+}
+
+pub fn unknown_command_message(name: &str) -> String {
+    let names: Vec<String> = command_definitions()
+        .iter()
+        .map(|c| format!(".{}", c.get_name()))
+        .collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+
+    match did_you_mean(name, &names) {
+        Some(suggestion) => format!("unknown command '{}', did you mean '{}'?", name, suggestion),
+        None => format!(
+            "unknown command '{}', type .help to list the commands",
+            name
+        ),
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+
+use chrono::DateTime;
+use clap::{CommandFactory, Parser};
+use deltalake::{
+    DeltaTable,
+    kernel::{Action, CommitInfo},
+};
+use serde_json::Value;
+
+use crate::{
+    program_context::ProgramContext,
+    utils::{args::parse_args, delta_log::read_commit_entry},
+};
+
+#[derive(Parser, Debug)]
+#[command(name = "history", about = "Display the commit log of a table")]
+struct HistoryArgs {
+    /// Table name
+    table_name: String,
+
+    /// Only display the latest N commits
+    #[arg(long)]
+    limit: Option<usize>,
+}
+
+pub fn command() -> clap::Command {
+    HistoryArgs::command()
+}
+
+pub async fn history_command(ctx: &ProgramContext, line: &str) -> Result<(), String> {
+    let Some(args) = parse_args::<HistoryArgs>(line)? else {
+        return Ok(());
+    };
+
+    let Some(table) = ctx.tables.get(&args.table_name) else {
+        return Err(format!("table '{}' not found", args.table_name));
+    };
+
+    for line in history_lines(table, args.limit).await? {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// The lines describing the commits of a table, from the latest, at most `limit` of them
+async fn history_lines(table: &DeltaTable, limit: Option<usize>) -> Result<Vec<String>, String> {
+    let mut lines = Vec::new();
+    // The version is taken from the log, as a commit doesn't always have commitInfo
+    let mut version = table.version().unwrap_or_default();
+    let mut shown = 0;
+    while version >= 0 && limit.is_none_or(|limit| shown < limit) {
+        let Some(actions) = read_commit_entry(table, version).await? else {
+            // The older commits were removed from the log
+            break;
+        };
+        let commit = actions
+            .into_iter()
+            .find_map(|action| match action {
+                Action::CommitInfo(info) => Some(info),
+                _ => None,
+            })
+            .unwrap_or_default();
+        lines.extend(format_commit(version, &commit));
+        shown += 1;
+        version -= 1;
+    }
+    Ok(lines)
+}
+
+fn format_commit(version: i64, commit: &CommitInfo) -> Vec<String> {
+    let timestamp = commit
+        .timestamp
+        .and_then(DateTime::from_timestamp_millis)
+        .map_or("unknown time".to_string(), |t| t.to_rfc3339());
+    let mut lines = vec![format!(
+        "Version {} at {}: {}",
+        version,
+        timestamp,
+        commit.operation.as_deref().unwrap_or("UNKNOWN")
+    )];
+
+    if let Some(user) = commit.user_name.as_ref().or(commit.user_id.as_ref()) {
+        lines.push(format!("  user: {}", user));
+    }
+    if let Some(parameters) = &commit.operation_parameters {
+        lines.push(format!("  parameters: {}", format_map(parameters)));
+    }
+    let engine = commit
+        .engine_info
+        .clone()
+        .or_else(|| commit.info.get("clientVersion").map(value_to_string));
+    if let Some(engine) = engine {
+        lines.push(format!("  engine: {}", engine));
+    }
+    if let Some(Value::Object(metrics)) = commit.info.get("operationMetrics") {
+        let metrics: HashMap<String, Value> = metrics.clone().into_iter().collect();
+        lines.push(format!("  metrics: {}", format_map(&metrics)));
+    }
+    lines
+}
+
+/// Formats a map as `key=value` pairs, sorted by key
+fn format_map(map: &HashMap<String, Value>) -> String {
+    let mut entries: Vec<String> = map
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, value_to_string(v)))
+        .collect();
+    entries.sort();
+    entries.join(", ")
+}
+
+/// JSON strings are displayed without quotes
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        commands::history::history_lines, program_context::ProgramContext, runner::run_query,
+        utils::test_tables::create_table,
+    };
+
+    /// The version and operation of every commit listed
+    async fn commits(ctx: &ProgramContext, limit: Option<usize>) -> Vec<String> {
+        history_lines(&ctx.tables["orders"], limit)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|line| line.starts_with("Version "))
+            .map(|line| {
+                let (version, operation) = line.split_once(" at ").unwrap();
+                let (_, operation) = operation.rsplit_once(": ").unwrap();
+                format!("{}: {}", version, operation)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_history_lines() {
+        let mut ctx = ProgramContext::new();
+        let _dir = create_table(&mut ctx, "orders").await;
+        run_query(&mut ctx, "INSERT INTO orders VALUES (1, 'pending');")
+            .await
+            .unwrap();
+        run_query(&mut ctx, "INSERT INTO orders VALUES (2, 'shipped');")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            commits(&ctx, None).await,
+            vec![
+                "Version 2: WRITE",
+                "Version 1: WRITE",
+                "Version 0: CREATE TABLE"
+            ]
+        );
+        assert_eq!(
+            commits(&ctx, Some(2)).await,
+            vec!["Version 2: WRITE", "Version 1: WRITE"]
+        );
+    }
+}
@@ -1,63 +1,462 @@
-use clap::Parser;
-use deltalake::{writer::{DeltaWriter, JsonWriter, WriteMode}, DeltaTable};
+use std::collections::BTreeSet;
+
+use clap::{CommandFactory, Parser, ValueEnum};
+use deltalake::{
+    DeltaTable, ObjectStore, Path,
+    arrow::{array::RecordBatch, datatypes::Schema},
+    datafusion::{
+        common::{Column, ScalarValue},
+        dataframe::DataFrame,
+        datasource::TableProvider,
+        execution::object_store::ObjectStoreUrl,
+        logical_expr::{Expr, cast, lit},
+        prelude::{CsvReadOptions, NdJsonReadOptions, ParquetReadOptions},
+    },
+    protocol::SaveMode,
+    writer::{DeltaWriter, JsonWriter, WriteMode},
+};
 use serde_json::Value;
 
-use crate::program_context::ProgramContext;
+use crate::{
+    program_context::ProgramContext, query::insert::write_batches, utils::args::parse_args,
+};
 
 #[derive(Parser, Debug)]
-#[command(name = "open", about = "Open a Delta table")]
+#[command(name = "insert", about = "Insert rows into a Delta table")]
 struct InsertArgs {
     /// Table name
     table_name: String,
 
     /// The values to be added in json format. Can be an object, representing
     /// a single row, or an array
+    #[arg(long, conflicts_with = "file", required_unless_present = "file")]
+    json: Option<String>,
+
+    /// Insert every row of a local file or directory, or of one in the same bucket as the table
     #[arg(long)]
-    json: String,
+    file: Option<String>,
+
+    /// Format of --file [default: from its extension]
+    #[arg(long, conflicts_with = "json")]
+    format: Option<FileFormat>,
 }
 
-pub async fn insert_command(ctx: &mut ProgramContext, line: &str) {
-    let args = match InsertArgs::try_parse_from(shell_words::split(line).expect("parse failed")) {
-        Ok(args) => args,
-        Err(e) => {
-            e.print().expect("error writing to stderr");
-            return;
-        }
-    };
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum FileFormat {
+    Csv,
+    Parquet,
+    /// One JSON object per line
+    Ndjson,
+    /// A JSON array of objects, or objects one after another
+    Json,
+}
 
-    let Some(table) = ctx.tables.get(&args.table_name) else {
-        eprintln!("Error: table '{}' not found", args.table_name);
-        return;
-    };
+impl FileFormat {
+    fn from_path(path: &str) -> Option<FileFormat> {
+        match file_extension(path)?.to_ascii_lowercase().as_str() {
+            ".csv" => Some(FileFormat::Csv),
+            ".parquet" => Some(FileFormat::Parquet),
+            ".ndjson" | ".jsonl" => Some(FileFormat::Ndjson),
+            ".json" => Some(FileFormat::Json),
+            _ => None,
+        }
+    }
 
-    let json: Value = match serde_json::from_str(&args.json) {
-        Ok(json) => json,
-        Err(e) => {
-            println!("{}", e);
-            return;
+    fn default_extension(&self) -> &'static str {
+        match self {
+            FileFormat::Csv => ".csv",
+            FileFormat::Parquet => ".parquet",
+            FileFormat::Ndjson | FileFormat::Json => ".json",
         }
-    };
+    }
+}
+
+pub fn command() -> clap::Command {
+    InsertArgs::command()
+}
 
-    let values: Vec<_> = if let Some(arr) = json.as_array() {
-        arr.iter().cloned().collect()
-    } else {
-        vec![json]
+pub async fn insert_command(ctx: &mut ProgramContext, line: &str) -> Result<(), String> {
+    let Some(args) = parse_args::<InsertArgs>(line)? else {
+        return Ok(());
     };
 
-    let len = values.len();
+    if !ctx.tables.contains_key(&args.table_name) {
+        return Err(format!("table '{}' not found", args.table_name));
+    }
+
+    match (args.json, args.file) {
+        (Some(json), _) => {
+            let json: Value = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+            let values: Vec<_> = if let Some(arr) = json.as_array() {
+                arr.iter().cloned().collect()
+            } else {
+                vec![json]
+            };
+            let len = values.len();
+            write_json(ctx, &args.table_name, values).await?;
+            println!("Written {} records", len);
+            Ok(())
+        }
+        (_, Some(file)) => insert_file(ctx, &args.table_name, &file, args.format).await,
+        _ => Err("either --json or --file is required".to_string()),
+    }
+}
 
-    // This clone is sloppy
-    let mut table: DeltaTable = table.as_ref().clone();
+async fn write_json(
+    ctx: &mut ProgramContext,
+    table_name: &str,
+    values: Vec<Value>,
+) -> Result<i64, String> {
+    let mut table = ctx.load_for_write(table_name).await?;
 
-    let mut writer = JsonWriter::for_table(&table).unwrap();
+    let mut writer = JsonWriter::for_table(&table).map_err(|e| e.to_string())?;
 
     writer
         .write_with_mode(values, WriteMode::Default)
         .await
-        .unwrap();
+        .map_err(|e| e.to_string())?;
+
+    let version = writer
+        .flush_and_commit(&mut table)
+        .await
+        .map_err(|e| e.to_string())?;
+    ctx.refresh_table(table_name, table).await;
+    Ok(version)
+}
+
+/// Reads a whole file, casts it to the table schema and writes it in a single commit.
+/// The rows are collected in memory before they are written, so the file has to fit in it
+async fn insert_file(
+    ctx: &mut ProgramContext,
+    table_name: &str,
+    path: &str,
+    format: Option<FileFormat>,
+) -> Result<(), String> {
+    let table = ctx.tables[table_name].clone();
+    let schema = TableProvider::schema(table.as_ref());
+    let format = format
+        .or_else(|| FileFormat::from_path(path))
+        .ok_or_else(|| format!("can't tell the format of '{}', use --format", path))?;
+
+    // Files in object stores are read with the storage options of the table
+    if let Some(bucket) = bucket_url(path) {
+        if bucket_url(&table.table_uri()) != Some(bucket) {
+            return Err(format!(
+                "'{}' has to be a local file, or be in the same bucket as the table",
+                path
+            ));
+        }
+        let url = ObjectStoreUrl::parse(bucket).map_err(|e| e.to_string())?;
+        ctx.df_ctx
+            .register_object_store(url.as_ref(), table.log_store().root_object_store(None));
+    }
+
+    let extension = file_extension(path).unwrap_or(format.default_extension());
+    let df = match format {
+        FileFormat::Csv => {
+            let options = CsvReadOptions::new().file_extension(extension);
+            ctx.df_ctx.read_csv(path, options).await
+        }
+        FileFormat::Parquet => {
+            let options = ParquetReadOptions {
+                file_extension: extension,
+                ..Default::default()
+            };
+            ctx.df_ctx.read_parquet(path, options).await
+        }
+        FileFormat::Ndjson => {
+            let options = NdJsonReadOptions::default().file_extension(extension);
+            ctx.df_ctx.read_json(path, options).await
+        }
+        FileFormat::Json => {
+            // The JSON writer already decodes the values with the table schema
+            let values = parse_json_values(&read_file(&table, path).await?)?;
+            print_notes(&json_mismatches(&values, &schema)?);
+            let rows = values.len();
+            let version = write_json(ctx, table_name, values).await?;
+            print_inserted(rows, path, table_name, version);
+            return Ok(());
+        }
+    }
+    .map_err(|e| e.to_string())?;
+
+    let (df, notes) = cast_to_schema(df, &schema)?;
+    print_notes(&notes);
+    let batches: Vec<RecordBatch> = df.collect().await.map_err(|e| e.to_string())?;
+    let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+    let version = write_batches(ctx, table_name, batches, SaveMode::Append).await?;
+    print_inserted(rows, path, table_name, version);
+    Ok(())
+}
+
+/// Selects the columns of the table from the ones read, casting the ones with another type.
+/// Returns a note for every column that didn't match
+fn cast_to_schema(df: DataFrame, schema: &Schema) -> Result<(DataFrame, Vec<String>), String> {
+    let extra: Vec<&str> = df
+        .schema()
+        .fields()
+        .iter()
+        .map(|f| f.name().as_str())
+        .filter(|name| schema.field_with_name(name).is_err())
+        .collect();
+    if !extra.is_empty() {
+        return Err(format!("columns not in the table: {}", extra.join(", ")));
+    }
+
+    let mut notes = Vec::new();
+    let mut columns = Vec::new();
+    for field in schema.fields() {
+        let column = match df.schema().field_with_unqualified_name(field.name()) {
+            Ok(read) => {
+                if read.data_type() != field.data_type() {
+                    notes.push(format!(
+                        "{}: cast from {} to {}",
+                        field.name(),
+                        read.data_type(),
+                        field.data_type()
+                    ));
+                }
+                Expr::Column(Column::new_unqualified(field.name()))
+            }
+            Err(_) if field.is_nullable() => {
+                notes.push(format!("{}: missing, written as NULL", field.name()));
+                lit(ScalarValue::Null)
+            }
+            Err(_) => return Err(format!("{}: missing, required column", field.name())),
+        };
+        columns.push(cast(column, field.data_type().clone()).alias(field.name()));
+    }
+
+    let df = df.select(columns).map_err(|e| e.to_string())?;
+    Ok((df, notes))
+}
+
+/// Checks the keys of JSON objects against the table schema. Returns a note
+/// for every nullable column no object has, and an error for a required one
+fn json_mismatches(values: &[Value], schema: &Schema) -> Result<Vec<String>, String> {
+    let mut keys = BTreeSet::new();
+    for value in values {
+        let Some(object) = value.as_object() else {
+            return Err(format!("expected JSON objects, found {}", value));
+        };
+        keys.extend(object.keys().map(|k| k.as_str()));
+    }
+
+    let extra: Vec<&str> = keys
+        .iter()
+        .copied()
+        .filter(|key| schema.field_with_name(key).is_err())
+        .collect();
+    if !extra.is_empty() {
+        return Err(format!("columns not in the table: {}", extra.join(", ")));
+    }
+
+    let missing = schema
+        .fields()
+        .iter()
+        .filter(|field| !keys.contains(field.name().as_str()));
+    let mut notes = Vec::new();
+    for field in missing {
+        if !field.is_nullable() {
+            return Err(format!("{}: missing, required column", field.name()));
+        }
+        notes.push(format!("{}: missing, written as NULL", field.name()));
+    }
+    Ok(notes)
+}
+
+/// Parses a JSON array, or JSON values one after another, like NDJSON
+fn parse_json_values(bytes: &[u8]) -> Result<Vec<Value>, String> {
+    let mut values = Vec::new();
+    for value in serde_json::Deserializer::from_slice(bytes).into_iter::<Value>() {
+        match value.map_err(|e| e.to_string())? {
+            Value::Array(array) => values.extend(array),
+            value => values.push(value),
+        }
+    }
+    Ok(values)
+}
+
+async fn read_file(table: &DeltaTable, path: &str) -> Result<Vec<u8>, String> {
+    let Some(bucket) = bucket_url(path) else {
+        return std::fs::read(path).map_err(|e| format!("{}: {}", path, e));
+    };
+    let location = Path::from(path[bucket.len()..].trim_start_matches('/'));
+    let file = table
+        .log_store()
+        .root_object_store(None)
+        .get(&location)
+        .await
+        .map_err(|e| e.to_string())?;
+    let bytes = file.bytes().await.map_err(|e| e.to_string())?;
+    Ok(bytes.to_vec())
+}
+
+/// The `scheme://bucket` part of an object store URL, or `None` for a local path
+fn bucket_url(path: &str) -> Option<&str> {
+    let (scheme, rest) = path.split_once("://")?;
+    if scheme == "file" {
+        return None;
+    }
+    let bucket = rest.split('/').next().unwrap_or(rest);
+    Some(&path[..scheme.len() + "://".len() + bucket.len()])
+}
 
-    writer.flush_and_commit(&mut table).await.unwrap();
-    ctx.refresh_table(&args.table_name, table).await;
+/// The extension of the last part of a path, with its dot
+fn file_extension(path: &str) -> Option<&str> {
+    let name = path.trim_end_matches('/').rsplit('/').next()?;
+    name.rfind('.').filter(|i| *i > 0).map(|i| &name[i..])
+}
+
+fn print_notes(notes: &[String]) {
+    for note in notes {
+        println!("  {}", note);
+    }
+}
 
-    println!("Written {} records", len);
+fn print_inserted(rows: usize, path: &str, table_name: &str, version: i64) {
+    println!(
+        "Inserted {} rows from {}, {} is now at version {}",
+        rows, path, table_name, version
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use deltalake::{
+        arrow::datatypes::{DataType, Field, Schema},
+        datafusion::{datasource::TableProvider, prelude::NdJsonReadOptions},
+    };
+    use serde_json::json;
+
+    use crate::{
+        commands::insert::{
+            FileFormat, bucket_url, cast_to_schema, file_extension, insert_command,
+            json_mismatches, parse_json_values,
+        },
+        program_context::ProgramContext,
+        utils::{
+            test_tables::{create_table, query_rows},
+            time_travel::TableVersion,
+        },
+    };
+
+    #[test]
+    fn test_file_format() {
+        assert_eq!(file_extension("data/orders.csv"), Some(".csv"));
+        assert_eq!(file_extension("s3://lake/orders/"), None);
+        assert_eq!(file_extension(".hidden"), None);
+        assert_eq!(
+            FileFormat::from_path("part-0.snappy.parquet"),
+            Some(FileFormat::Parquet)
+        );
+        assert_eq!(
+            FileFormat::from_path("events.JSONL"),
+            Some(FileFormat::Ndjson)
+        );
+        assert_eq!(FileFormat::from_path("events.json"), Some(FileFormat::Json));
+        assert_eq!(FileFormat::from_path("events"), None);
+    }
+
+    #[test]
+    fn test_bucket_url() {
+        assert_eq!(bucket_url("s3://lake/raw/orders.csv"), Some("s3://lake"));
+        assert_eq!(bucket_url("s3://lake"), Some("s3://lake"));
+        assert_eq!(bucket_url("file:///tmp/orders.csv"), None);
+        assert_eq!(bucket_url("./orders.csv"), None);
+    }
+
+    #[test]
+    fn test_parse_json_values() {
+        let expected = vec![json!({"id": 1}), json!({"id": 2})];
+        assert_eq!(
+            parse_json_values(br#"[{"id": 1}, {"id": 2}]"#),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            parse_json_values(b"{\"id\": 1}\n{\"id\": 2}\n"),
+            Ok(expected)
+        );
+        assert!(parse_json_values(b"{\"id\": ").is_err());
+    }
+
+    #[test]
+    fn test_json_mismatches() {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("status", DataType::Utf8, true),
+        ]);
+        assert_eq!(
+            json_mismatches(&[json!({"id": 1})], &schema),
+            Ok(vec!["status: missing, written as NULL".to_string()])
+        );
+        assert_eq!(
+            json_mismatches(&[json!({"status": "lost"})], &schema),
+            Err("id: missing, required column".to_string())
+        );
+        assert_eq!(
+            json_mismatches(&[json!({"id": 1, "amount": 2})], &schema),
+            Err("columns not in the table: amount".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insert_json_pinned() {
+        let mut ctx = ProgramContext::new();
+        let _dir = create_table(&mut ctx, "orders").await;
+        ctx.set_pin("orders", Some(TableVersion::Version(0)));
+
+        let err = insert_command(&mut ctx, r#".insert orders --json '{"id": 1}'"#)
+            .await
+            .unwrap_err();
+        assert!(err.contains("pinned"), "{}", err);
+        assert_eq!(ctx.load_latest("orders").await.unwrap().version(), Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_insert_file() {
+        let mut ctx = ProgramContext::new();
+        let dir = create_table(&mut ctx, "orders").await;
+        let path = dir.path().join("orders.ndjson");
+        std::fs::write(&path, "{\"id\": \"1\"}\n{\"id\": \"2\"}\n").unwrap();
+        let path = path.to_str().unwrap();
+
+        let df = ctx
+            .df_ctx
+            .read_json(path, NdJsonReadOptions::default().file_extension(".ndjson"))
+            .await
+            .unwrap();
+        let schema = TableProvider::schema(ctx.tables["orders"].as_ref());
+        let (_, notes) = cast_to_schema(df, &schema).unwrap();
+        assert_eq!(notes.len(), 2, "{:?}", notes);
+        assert!(notes[0].starts_with("id: cast from Utf8"), "{}", notes[0]);
+        assert!(notes[0].ends_with("to Int64"), "{}", notes[0]);
+        assert_eq!(notes[1], "status: missing, written as NULL");
+
+        insert_command(&mut ctx, &format!(".insert orders --file {}", path))
+            .await
+            .unwrap();
+        assert_eq!(ctx.tables["orders"].version(), Some(1));
+        assert_eq!(
+            query_rows(&ctx, "SELECT * FROM orders").await,
+            vec!["1, NULL", "2, NULL"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insert_file_extra_columns() {
+        let mut ctx = ProgramContext::new();
+        let dir = create_table(&mut ctx, "orders").await;
+        let path = dir.path().join("orders.csv");
+        std::fs::write(&path, "id,status,amount\n1,pending,10\n").unwrap();
+
+        let err = insert_command(
+            &mut ctx,
+            &format!(".insert orders --file {}", path.to_str().unwrap()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err, "columns not in the table: amount");
+        assert_eq!(ctx.tables["orders"].version(), Some(0));
+    }
 }
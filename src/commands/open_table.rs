@@ -1,9 +1,15 @@
-use std::{collections::HashMap, sync::Arc};
-
-use clap::{Parser, arg, command};
+use chrono::{DateTime, Utc};
+use clap::{CommandFactory, Parser};
 use deltalake::open_table_with_storage_options;
 
-use crate::program_context::ProgramContext;
+use crate::{
+    program_context::ProgramContext,
+    storage::options::StorageArgs,
+    utils::{
+        args::parse_args,
+        time_travel::{TableVersion, parse_timestamp},
+    },
+};
 
 #[derive(Parser, Debug)]
 #[command(name = "open", about = "Open a Delta table")]
@@ -14,65 +20,62 @@ struct OpenArgs {
     /// s3://bucket/path
     table_path: String,
 
-    /// http://host:port for MinIO / S3 endpoint
-    #[arg(long)]
-    endpoint_url: Option<String>,
-
-    /// Access key
-    #[arg(long)]
-    access_key_id: Option<String>,
-
-    /// Secret key
-    #[arg(long)]
-    secret_access_key: Option<String>,
+    /// Open the table as it was at this version
+    #[arg(long, conflicts_with = "timestamp")]
+    version: Option<i64>,
 
-    /// true/false
-    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
-    allow_http: bool,
+    /// Open the table as it was at this time, RFC 3339 or YYYY-MM-DD
+    #[arg(long, value_parser = parse_timestamp)]
+    timestamp: Option<DateTime<Utc>>,
 
-    /// S3 addressing style: "path" or "virtual"
-    #[arg(long, value_parser = ["path", "virtual"], default_value = "path")]
-    addressing_style: String,
+    #[command(flatten)]
+    storage: StorageArgs,
+}
 
-    /// Conditional write mechanism: "etag" (MinIO) or "dynamodb" (AWS)
-    #[arg(long, value_parser = ["etag", "dynamodb"], default_value = "etag")]
-    conditional_put: String,
+pub fn command() -> clap::Command {
+    OpenArgs::command()
 }
 
-pub async fn open_table_command(ctx: &mut ProgramContext, line: &str) {
-    let args = match OpenArgs::try_parse_from(shell_words::split(line).expect("parse failed")) {
-        Ok(args) => args,
-        Err(e) => {
-            e.print().expect("error writing to stderr");
-            return;
-        }
+pub async fn open_table_command(ctx: &mut ProgramContext, line: &str) -> Result<(), String> {
+    let Some(args) = parse_args::<OpenArgs>(line)? else {
+        return Ok(());
     };
 
-    let mut storage_options: HashMap<String, String> = HashMap::new();
-
-    if let Some(v) = args.endpoint_url {
-        storage_options.insert("AWS_ENDPOINT_URL".into(), v);
-    }
-    if let Some(v) = args.access_key_id {
-        storage_options.insert("AWS_ACCESS_KEY_ID".into(), v);
-    }
-    if let Some(v) = args.secret_access_key {
-        storage_options.insert("AWS_SECRET_ACCESS_KEY".into(), v);
-    }
+    let version = match (args.version, args.timestamp) {
+        (Some(version), _) => Some(TableVersion::Version(version)),
+        (_, Some(timestamp)) => Some(TableVersion::Timestamp(timestamp)),
+        _ => None,
+    };
 
-    storage_options.insert("AWS_ALLOW_HTTP".into(), args.allow_http.to_string());
-    storage_options.insert("AWS_S3_ADDRESSING_STYLE".into(), args.addressing_style);
-    storage_options.insert("aws_conditional_put".into(), args.conditional_put);
+    open_table(
+        ctx,
+        &args.table_name,
+        &args.table_path,
+        &args.storage,
+        version,
+    )
+    .await
+}
 
-    let table = match open_table_with_storage_options(args.table_path, storage_options).await {
-        Ok(table) => Arc::new(table),
-        Err(e) => {
-            println!("{}", e);
-            return;
-        }
-    };
+/// Opens the table at `table_path` and registers it as `table_name`.
+/// With a `version`, the table is pinned to that snapshot instead of the latest one.
+pub async fn open_table(
+    ctx: &mut ProgramContext,
+    table_name: &str,
+    table_path: &str,
+    storage: &StorageArgs,
+    version: Option<TableVersion>,
+) -> Result<(), String> {
+    let storage = storage.resolve()?;
+    let mut table = open_table_with_storage_options(table_path, storage.storage_options())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if let Some(version) = &version {
+        version.load(&mut table).await.map_err(|e| e.to_string())?;
+    }
 
-    ctx.tables.insert(args.table_name.clone(), table.clone());
-    ctx.df_ctx.register_table(args.table_name, table).unwrap();
-    // TODO remove uwnrap
+    ctx.refresh_table(table_name, table).await;
+    ctx.set_pin(table_name, version);
+    Ok(())
 }
@@ -0,0 +1,37 @@
+use clap::{CommandFactory, Parser};
+
+use crate::{
+    storage::profiles::{load_profiles, profiles_path},
+    utils::args::parse_args,
+};
+
+#[derive(Parser, Debug)]
+#[command(name = "profiles", about = "List the storage profiles")]
+struct ProfilesArgs {}
+
+pub fn command() -> clap::Command {
+    ProfilesArgs::command()
+}
+
+pub fn profiles_command(line: &str) -> Result<(), String> {
+    let Some(_args) = parse_args::<ProfilesArgs>(line)? else {
+        return Ok(());
+    };
+
+    let profiles = load_profiles()?;
+    if profiles.is_empty() {
+        match profiles_path() {
+            Some(path) => println!("No profiles configured in {}", path.display()),
+            None => println!("No profiles configured"),
+        }
+        return Ok(());
+    }
+
+    for (name, profile) in profiles {
+        println!("{}", name);
+        for (option, value) in profile.masked_entries() {
+            println!("  {}: {}", option, value);
+        }
+    }
+    Ok(())
+}
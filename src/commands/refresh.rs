@@ -0,0 +1,80 @@
+use clap::{CommandFactory, Parser};
+
+use crate::{
+    program_context::ProgramContext,
+    utils::{args::parse_args, time_travel::format_version},
+};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "refresh",
+    about = "Update opened tables to their latest version"
+)]
+struct RefreshArgs {
+    /// Table name [default: every table that isn't pinned]
+    table_name: Option<String>,
+}
+
+pub fn command() -> clap::Command {
+    RefreshArgs::command()
+}
+
+pub async fn refresh_command(ctx: &mut ProgramContext, line: &str) -> Result<(), String> {
+    let Some(args) = parse_args::<RefreshArgs>(line)? else {
+        return Ok(());
+    };
+
+    let table_names = match args.table_name {
+        Some(table_name) => {
+            if let Some(version) = ctx.pins.get(&table_name) {
+                return Err(format!(
+                    "table '{}' is pinned to {}, use .checkout {} latest to unpin it",
+                    table_name, version, table_name
+                ));
+            }
+            vec![table_name]
+        }
+        None => unpinned_tables(ctx),
+    };
+
+    for table_name in table_names {
+        let (before, after) = ctx.update_table(&table_name).await?;
+        if before == after {
+            println!("{} is up to date ({})", table_name, format_version(after));
+        } else {
+            print_moved(&table_name, before, after);
+        }
+    }
+    Ok(())
+}
+
+/// Updates every table that isn't pinned, and reports the ones that moved forward
+pub async fn auto_refresh(ctx: &mut ProgramContext) -> Result<(), String> {
+    for table_name in unpinned_tables(ctx) {
+        let (before, after) = ctx.update_table(&table_name).await?;
+        if before != after {
+            print_moved(&table_name, before, after);
+        }
+    }
+    Ok(())
+}
+
+fn unpinned_tables(ctx: &ProgramContext) -> Vec<String> {
+    let mut table_names: Vec<String> = ctx
+        .tables
+        .keys()
+        .filter(|table_name| !ctx.pins.contains_key(*table_name))
+        .cloned()
+        .collect();
+    table_names.sort();
+    table_names
+}
+
+fn print_moved(table_name: &str, before: Option<i64>, after: Option<i64>) {
+    println!(
+        "{} moved from {} to {}",
+        table_name,
+        format_version(before),
+        format_version(after)
+    );
+}
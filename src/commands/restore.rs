@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use clap::{CommandFactory, Parser};
+use deltalake::DeltaOps;
+
+use crate::{
+    program_context::ProgramContext,
+    utils::{args::parse_args, time_travel::parse_timestamp},
+};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "restore",
+    about = "Restore a table to an earlier version with a new commit"
+)]
+struct RestoreArgs {
+    /// Table name
+    table_name: String,
+
+    /// Version to restore
+    #[arg(
+        long,
+        conflicts_with = "timestamp",
+        required_unless_present = "timestamp"
+    )]
+    version: Option<i64>,
+
+    /// Restore the table as it was at this time, RFC 3339 or YYYY-MM-DD
+    #[arg(long, value_parser = parse_timestamp)]
+    timestamp: Option<DateTime<Utc>>,
+}
+
+pub fn command() -> clap::Command {
+    RestoreArgs::command()
+}
+
+pub async fn restore_command(ctx: &mut ProgramContext, line: &str) -> Result<(), String> {
+    let Some(args) = parse_args::<RestoreArgs>(line)? else {
+        return Ok(());
+    };
+
+    let table = ctx.load_latest(&args.table_name).await?;
+    let mut restore = DeltaOps::from(table).restore();
+    let target = match (args.version, args.timestamp) {
+        (Some(version), _) => {
+            restore = restore.with_version_to_restore(version);
+            format!("version {}", version)
+        }
+        (_, Some(timestamp)) => {
+            restore = restore.with_datetime_to_restore(timestamp);
+            timestamp.to_rfc3339()
+        }
+        _ => return Err("either --version or --timestamp is required".to_string()),
+    };
+    let (table, metrics) = restore.await.map_err(|e| e.to_string())?;
+
+    println!(
+        "Restored {} to {}: {} files removed, {} files restored",
+        args.table_name, target, metrics.num_removed_file, metrics.num_restored_file
+    );
+    if let Some(version) = table.version() {
+        println!("{} is now at version {}", args.table_name, version);
+    }
+
+    ctx.refresh_table(&args.table_name, table).await;
+    ctx.set_pin(&args.table_name, None);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        commands::{checkout::checkout_command, restore::restore_command},
+        program_context::ProgramContext,
+        runner::run_query,
+        utils::test_tables::{create_table, query_rows},
+    };
+
+    #[tokio::test]
+    async fn test_restore_version() {
+        let mut ctx = ProgramContext::new();
+        let _dir = create_table(&mut ctx, "orders").await;
+        run_query(&mut ctx, "INSERT INTO orders VALUES (1, 'pending');")
+            .await
+            .unwrap();
+        run_query(&mut ctx, "INSERT INTO orders VALUES (2, 'shipped');")
+            .await
+            .unwrap();
+        checkout_command(&mut ctx, ".checkout orders 1")
+            .await
+            .unwrap();
+        assert!(ctx.pins.contains_key("orders"));
+
+        restore_command(&mut ctx, ".restore orders --version 1")
+            .await
+            .unwrap();
+        assert_eq!(ctx.tables["orders"].version(), Some(3));
+        assert!(!ctx.pins.contains_key("orders"));
+        assert_eq!(
+            query_rows(&ctx, "SELECT * FROM orders").await,
+            vec!["1, pending"]
+        );
+    }
+}
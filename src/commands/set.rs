@@ -0,0 +1,41 @@
+use clap::{CommandFactory, Parser};
+
+use crate::{program_context::ProgramContext, settings::Settings, utils::args::parse_args};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "set",
+    about = "Change a session setting, or display them all",
+    after_help = "Settings:\n  \
+        auto_refresh  Check the opened tables for new commits before every query [on|off]\n  \
+        force_delete  Run a DELETE without WHERE without asking for confirmation [on|off]"
+)]
+struct SetArgs {
+    /// Setting name
+    name: Option<String>,
+
+    /// New value. Without it, the current value is displayed
+    #[arg(requires = "name")]
+    value: Option<String>,
+}
+
+pub fn command() -> clap::Command {
+    SetArgs::command()
+}
+
+pub fn set_command(ctx: &mut ProgramContext, line: &str) -> Result<(), String> {
+    let Some(args) = parse_args::<SetArgs>(line)? else {
+        return Ok(());
+    };
+
+    match (args.name, args.value) {
+        (Some(name), Some(value)) => ctx.settings.set(&name, &value)?,
+        (Some(name), None) => println!("{} = {}", name, ctx.settings.get(&name)?),
+        _ => {
+            for name in Settings::NAMES {
+                println!("{} = {}", name, ctx.settings.get(name)?);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use clap::{CommandFactory, Parser};
+
+use crate::{
+    program_context::ProgramContext,
+    utils::{
+        args::parse_args,
+        time_travel::{TableVersion, format_version},
+    },
+};
+
+#[derive(Parser, Debug)]
+#[command(name = "tables", about = "Display all opened tables")]
+struct TablesArgs {}
+
+pub fn command() -> clap::Command {
+    TablesArgs::command()
+}
+
+pub fn tables_command(ctx: &ProgramContext, line: &str) -> Result<(), String> {
+    let Some(_args) = parse_args::<TablesArgs>(line)? else {
+        return Ok(());
+    };
+
+    for table_name in ctx.table_names() {
+        let Some(table) = ctx.tables.get(&table_name) else {
+            println!("{}", table_name);
+            continue;
+        };
+        let version = format_version(table.version());
+        match ctx.pins.get(&table_name) {
+            Some(TableVersion::Timestamp(timestamp)) => println!(
+                "{} ({}, pinned to {})",
+                table_name,
+                version,
+                timestamp.to_rfc3339()
+            ),
+            Some(_) => println!("{} ({}, pinned)", table_name, version),
+            None => println!("{} ({})", table_name, version),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use std::{sync::Arc, time::Duration};
+
+use clap::{CommandFactory, Parser};
+use deltalake::{
+    DeltaTable,
+    datafusion::dataframe::DataFrame,
+    delta_datafusion::{DeltaScanConfigBuilder, DeltaTableProvider},
+    kernel::{Action, Add},
+};
+
+use crate::{
+    program_context::ProgramContext,
+    utils::{
+        args::parse_args, delta_log::read_commit, duration::parse_duration,
+        time_travel::TableVersion,
+    },
+};
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "tail",
+    about = "Follow a table, displaying the rows added by each new commit until Ctrl-C"
+)]
+struct TailArgs {
+    /// Table name
+    table_name: String,
+
+    /// How often to check for new commits, e.g. 500ms, 5s or 1m
+    #[arg(long, default_value = "5s", value_parser = parse_duration)]
+    interval: Duration,
+
+    /// Only display the rows matching this SQL predicate
+    #[arg(long = "where", value_name = "EXPR")]
+    filter: Option<String>,
+}
+
+pub fn command() -> clap::Command {
+    TailArgs::command()
+}
+
+pub async fn tail_command(ctx: &ProgramContext, line: &str) -> Result<(), String> {
+    let Some(args) = parse_args::<TailArgs>(line)? else {
+        return Ok(());
+    };
+
+    let mut version = ctx.load_latest(&args.table_name).await?.version();
+    match version {
+        Some(version) => println!(
+            "Following {} from version {}, press Ctrl-C to stop",
+            args.table_name, version
+        ),
+        None => println!("Following {}, press Ctrl-C to stop", args.table_name),
+    }
+
+    // Created once, so a Ctrl-C while a commit is being displayed isn't missed
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    loop {
+        tokio::select! {
+            _ = &mut ctrl_c => break,
+            _ = tokio::time::sleep(args.interval) => {}
+        }
+
+        let table = ctx.load_latest(&args.table_name).await?;
+        let Some(latest) = table.version() else {
+            continue;
+        };
+        let first = version.map_or(0, |v| v + 1);
+        for commit_version in first..=latest {
+            let filter = args.filter.as_deref();
+            print_commit(ctx, &args.table_name, &table, commit_version, filter).await?;
+        }
+        version = Some(latest);
+    }
+    Ok(())
+}
+
+/// Displays the rows in the files added by a commit
+async fn print_commit(
+    ctx: &ProgramContext,
+    table_name: &str,
+    table: &DeltaTable,
+    version: i64,
+    filter: Option<&str>,
+) -> Result<(), String> {
+    let (operation, files) = added_files(table, version).await?;
+    println!(
+        "Version {} ({}): {} files added",
+        version,
+        operation.as_deref().unwrap_or("UNKNOWN"),
+        files.len()
+    );
+    if files.is_empty() {
+        return Ok(());
+    }
+
+    let df = read_files(ctx, table_name, version, files, filter).await?;
+    println!("{}", df.to_string().await.map_err(|e| e.to_string())?);
+    Ok(())
+}
+
+/// The operation of a commit, and the files it added with new data. Files that only
+/// rearrange existing data, like the ones written by OPTIMIZE, are skipped
+async fn added_files(
+    table: &DeltaTable,
+    version: i64,
+) -> Result<(Option<String>, Vec<Add>), String> {
+    let mut operation = None;
+    let mut files = Vec::new();
+    for action in read_commit(table, version).await? {
+        match action {
+            Action::CommitInfo(info) => operation = info.operation,
+            Action::Add(add) if add.data_change => files.push(add),
+            _ => {}
+        }
+    }
+    Ok((operation, files))
+}
+
+/// Reads the rows of some files of a table, with the snapshot at the version that added them.
+/// Later commits may have removed the files already
+async fn read_files(
+    ctx: &ProgramContext,
+    table_name: &str,
+    version: i64,
+    files: Vec<Add>,
+    filter: Option<&str>,
+) -> Result<DataFrame, String> {
+    let table = ctx
+        .load_snapshot(table_name, &TableVersion::Version(version))
+        .await?;
+    let snapshot = table.snapshot().map_err(|e| e.to_string())?;
+    let config = DeltaScanConfigBuilder::new()
+        .build(snapshot)
+        .map_err(|e| e.to_string())?;
+    let provider = DeltaTableProvider::try_new(snapshot.clone(), table.log_store(), config)
+        .map_err(|e| e.to_string())?
+        .with_files(files);
+
+    let mut df = ctx
+        .df_ctx
+        .read_table(Arc::new(provider))
+        .map_err(|e| e.to_string())?;
+    if let Some(filter) = filter {
+        let predicate = df.parse_sql_expr(filter).map_err(|e| e.to_string())?;
+        df = df.filter(predicate).map_err(|e| e.to_string())?;
+    }
+    Ok(df)
+}
+
+#[cfg(test)]
+mod test {
+    use deltalake::DeltaOps;
+
+    use crate::{
+        commands::tail::{added_files, read_files},
+        program_context::ProgramContext,
+        runner::run_query,
+        utils::test_tables::{create_table, rows},
+    };
+
+    #[tokio::test]
+    async fn test_rows_of_compacted_files() {
+        let mut ctx = ProgramContext::new();
+        let _dir = create_table(&mut ctx, "orders").await;
+        run_query(&mut ctx, "INSERT INTO orders VALUES (1, 'pending');")
+            .await
+            .unwrap();
+        run_query(&mut ctx, "INSERT INTO orders VALUES (2, 'shipped');")
+            .await
+            .unwrap();
+        // Version 3 replaces the files of versions 1 and 2 before the next poll
+        let table = ctx.load_latest("orders").await.unwrap();
+        let (table, _) = DeltaOps::from(table).optimize().await.unwrap();
+        assert_eq!(table.version(), Some(3));
+
+        let (_, files) = added_files(&table, 1).await.unwrap();
+        let df = read_files(&ctx, "orders", 1, files, None).await.unwrap();
+        assert_eq!(rows(df).await, vec!["1, pending"]);
+
+        let (_, files) = added_files(&table, 2).await.unwrap();
+        let df = read_files(&ctx, "orders", 2, files.clone(), Some("status = 'shipped'"))
+            .await
+            .unwrap();
+        assert_eq!(rows(df).await, vec!["2, shipped"]);
+        let df = read_files(&ctx, "orders", 2, files, Some("id = 1"))
+            .await
+            .unwrap();
+        assert!(rows(df).await.is_empty());
+
+        let (operation, files) = added_files(&table, 3).await.unwrap();
+        assert_eq!(operation.as_deref(), Some("OPTIMIZE"));
+        assert!(files.is_empty());
+    }
+}
@@ -1,18 +1,51 @@
-use commands::create_table::create_table_command;
-use commands::display_schema::display_schema_command;
-use commands::insert::insert_command;
-use commands::open_table::open_table_command;
-use deltalake::datafusion::error::DataFusionError;
-use indoc::indoc;
+use std::fs::File;
+use std::io::{BufReader, Cursor, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::Parser;
+use commands::open_table::open_table;
 use program_context::ProgramContext;
+use repl::helper::ReplHelper;
+use runner::{Statement, StatementBuffer, run_script, run_statement};
+use rustyline::Editor;
 use rustyline::error::ReadlineError;
-use rustyline::{DefaultEditor, Result};
+use rustyline::history::DefaultHistory;
+use storage::options::StorageArgs;
+use utils::history::{create_history_file, history_path, redact_secrets};
 
 pub mod commands {
+    pub mod changes;
+    pub mod checkout;
     pub mod create_table;
+    pub mod definitions;
+    pub mod diff;
+    pub mod diff_files;
     pub mod display_schema;
+    pub mod help;
+    pub mod history;
     pub mod insert;
     pub mod open_table;
+    pub mod profiles;
+    pub mod refresh;
+    pub mod restore;
+    pub mod set;
+    pub mod tables;
+    pub mod tail;
+}
+
+pub mod query {
+    pub mod delete;
+    pub mod insert;
+    pub mod merge;
+    pub mod time_travel;
+    pub mod update;
+}
+
+pub mod repl {
+    pub mod completion;
+    pub mod helper;
+    pub mod highlight;
 }
 
 pub mod schema {
@@ -20,51 +53,198 @@ pub mod schema {
     pub mod tokenizer;
 }
 
+pub mod storage {
+    pub mod options;
+    pub mod profiles;
+}
+
 pub mod utils {
+    pub mod args;
     pub mod data_type;
+    pub mod delta_log;
+    pub mod duration;
+    pub mod history;
+    pub mod sql;
+    pub mod suggest;
+    #[cfg(test)]
+    pub mod test_tables;
+    pub mod time_travel;
 }
 
 pub mod program_context;
+pub mod runner;
+pub mod settings;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "deltaq",
+    about = "A simple terminal client for Delta Lake tables"
+)]
+struct Cli {
+    /// Open a table before starting the REPL. Can be repeated
+    #[arg(long, value_name = "TABLE_NAME=TABLE_PATH", value_parser = parse_open_arg)]
+    open: Vec<(String, String)>,
+
+    /// Run a single command or SQL statement and exit
+    #[arg(short = 'c', long = "command", conflicts_with = "file")]
+    command: Option<String>,
+
+    /// Run the commands and SQL statements in a file and exit
+    #[arg(short = 'f', long = "file")]
+    file: Option<PathBuf>,
+
+    /// Startup file to run instead of ~/.deltaqrc and ./.deltaqrc
+    #[arg(long, value_name = "PATH", conflicts_with = "no_rc")]
+    rc: Option<PathBuf>,
+
+    /// Don't run any startup file
+    #[arg(long)]
+    no_rc: bool,
+
+    /// Storage options used for every table given with --open
+    #[command(flatten)]
+    storage: StorageArgs,
+}
+
+fn parse_open_arg(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, path)) if !name.is_empty() && !path.is_empty() => {
+            Ok((name.to_string(), path.to_string()))
+        }
+        _ => Err(format!("expected TABLE_NAME=TABLE_PATH, got '{}'", s)),
+    }
+}
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+
     deltalake::aws::register_handlers(None);
     let mut ctx = ProgramContext::new();
 
-    let mut sb = "".to_string();
+    for (table_name, table_path) in &cli.open {
+        match open_table(&mut ctx, table_name, table_path, &cli.storage, None).await {
+            Ok(()) => println!("Opened {} from {}", table_name, table_path),
+            Err(e) => {
+                eprintln!("Error opening {}: {}", table_name, e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    if !cli.no_rc {
+        run_rc_files(&mut ctx, &cli).await;
+    }
+
+    let result = if let Some(command) = &cli.command {
+        run_script(&mut ctx, Cursor::new(command), false)
+            .await
+            .map(|_| ())
+    } else if let Some(path) = &cli.file {
+        match File::open(path) {
+            Ok(file) => run_script(&mut ctx, BufReader::new(file), false)
+                .await
+                .map(|_| ()),
+            Err(e) => Err(format!("{}: {}", path.display(), e)),
+        }
+    } else if !std::io::stdin().is_terminal() {
+        run_script(&mut ctx, std::io::stdin().lock(), false)
+            .await
+            .map(|_| ())
+    } else {
+        ctx.interactive = true;
+        repl(&mut ctx).await.map_err(|e| e.to_string())
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the startup files, after the tables given with --open are opened. Errors are reported
+/// but don't stop deltaq
+async fn run_rc_files(ctx: &mut ProgramContext, cli: &Cli) {
+    let paths = match &cli.rc {
+        Some(path) => vec![path.clone()],
+        None => {
+            let mut paths = Vec::new();
+            if let Some(home) = dirs::home_dir() {
+                paths.push(home.join(".deltaqrc"));
+            }
+            let local = PathBuf::from(".deltaqrc");
+            if !paths.iter().any(|p| same_file(p, &local)) {
+                paths.push(local);
+            }
+            paths.into_iter().filter(|p| p.is_file()).collect()
+        }
+    };
+
+    for path in paths {
+        let result = match File::open(&path) {
+            Ok(file) => run_script(ctx, BufReader::new(file), true)
+                .await
+                .map(|_| ()),
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(e) = result {
+            eprintln!("Error in {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+async fn repl(ctx: &mut ProgramContext) -> rustyline::Result<()> {
+    let mut sb = StatementBuffer::new();
+
+    let mut rl: Editor<ReplHelper, DefaultHistory> = Editor::new()?;
+    rl.set_helper(Some(ReplHelper::new()));
+    let history_path = history_path();
+    if let Some(path) = &history_path {
+        match create_history_file(path) {
+            Ok(()) => {
+                let _ = rl.load_history(path);
+            }
+            Err(e) => eprintln!("Error opening history file {}: {}", path.display(), e),
+        }
+    }
 
-    let mut rl = DefaultEditor::new()?;
     loop {
         let prompt = if sb.is_empty() {
             "deltaq> "
         } else {
             "   ...> "
         };
+        if let Some(helper) = rl.helper_mut() {
+            helper.update(ctx);
+            helper.set_pending_sql(sb.pending_sql());
+        }
         let readline = rl.readline(prompt);
         match readline {
-            Ok(line) if matches!(line.trim(), "quit" | "exit" | "\\q") => break,
-            Ok(line) if line.starts_with(".") => {
-                rl.add_history_entry(line.as_str())?;
-                run_command(&mut ctx, &line).await;
-            }
             Ok(line) => {
-                if sb.is_empty() {
-                    sb = line
-                } else {
-                    sb = format!("{}\n{}", sb, &line);
-                }
-                if sb.trim_end().ends_with(";") {
-                    let query = sb;
-                    sb = String::new();
-
-                    rl.add_history_entry(&query)?;
-                    let res = ctx.df_ctx.sql(&query).await;
-                    match res {
-                        Ok(df) => println!("{}", df.to_string().await.unwrap()),
-                        Err(e) => match e {
-                            DataFusionError::SQL(e, _) => println!("{}", e),
-                            _ => println!("{}", e),
-                        },
+                if let Some(statement) = sb.push(&line) {
+                    match &statement {
+                        Statement::Command(line) => rl.add_history_entry(redact_secrets(line))?,
+                        Statement::Sql(query) => rl.add_history_entry(query.as_str())?,
+                        Statement::Quit => break,
+                    };
+                    // Saved right away, so the entry is kept if deltaq is killed
+                    if let Some(path) = &history_path
+                        && let Err(e) = rl.append_history(path)
+                    {
+                        eprintln!("Error saving history to {}: {}", path.display(), e);
+                    }
+                    if let Err(e) = run_statement(ctx, statement).await {
+                        eprintln!("Error: {}", e);
                     }
                 }
             }
@@ -72,7 +252,7 @@ async fn main() -> Result<()> {
                 println!("^C");
             }
             Err(ReadlineError::Eof) => {
-                println!("");
+                println!();
                 break;
             }
             Err(err) => {
@@ -81,52 +261,29 @@ async fn main() -> Result<()> {
             }
         }
     }
+
     Ok(())
 }
 
-async fn run_command(ctx: &mut ProgramContext, line: &str) {
-    let args = shell_words::split(line).expect("parse failed");
-
-    match args[0].as_str() {
-        ".help" => {
-            print!(indoc!(
-                r#"
-                Commands: (type any command for more help about the specific command)
-                .open <TABLE_NAME> <TABLE_PATH>
-                    Open a table at a path, and give it a name.
-                    For more info, like specifying S3 see the .open help
-                .create --schema <schema> <TABLE_NAME> <TABLE_PATH> 
-                    Create a table from a given schema
-                .tables
-                    Display all opened tables
-                .schema <TABLE_NAME>
-                    Display the schema for a given table
-            "#
+#[cfg(test)]
+mod test {
+    use crate::parse_open_arg;
+
+    #[test]
+    fn test_parse_open_arg() {
+        assert_eq!(
+            parse_open_arg("sales=s3://lake/sales"),
+            Ok(("sales".to_string(), "s3://lake/sales".to_string()))
+        );
+        assert_eq!(
+            parse_open_arg("sales=s3://lake/sales/day=2026-01-01"),
+            Ok((
+                "sales".to_string(),
+                "s3://lake/sales/day=2026-01-01".to_string()
             ))
-        }
-        ".open" => {
-            open_table_command(ctx, line).await;
-        }
-        ".create" => {
-            create_table_command(ctx, line).await;
-        }
-        ".tables" => {
-            for table_name in &ctx.df_ctx
-                .catalog("datafusion")
-                .unwrap()
-                .schema("public")
-                .unwrap()
-                .table_names()
-            {
-                println!("{}", table_name);
-            }
-        }
-        ".schema" => {
-            display_schema_command(&ctx.df_ctx, line).await;
-        }
-        ".insert" => {
-            insert_command(ctx, line).await;
-        }
-        _ => {}
+        );
+        assert!(parse_open_arg("s3://lake/sales").is_err());
+        assert!(parse_open_arg("=s3://lake/sales").is_err());
+        assert!(parse_open_arg("sales=").is_err());
     }
 }
@@ -2,9 +2,16 @@ use std::sync::Arc;
 
 use deltalake::{datafusion::{common::HashMap, prelude::SessionContext}, DeltaTable};
 
+use crate::{settings::Settings, utils::time_travel::TableVersion};
+
 pub struct ProgramContext {
     pub df_ctx: SessionContext,
     pub tables: HashMap<String, Arc<DeltaTable>>,
+    /// Tables pinned to a snapshot other than the latest one
+    pub pins: HashMap<String, TableVersion>,
+    pub settings: Settings,
+    /// True when running the REPL, so the user can be asked for confirmation
+    pub interactive: bool,
 }
 
 impl ProgramContext {
@@ -12,7 +19,85 @@ impl ProgramContext {
         ProgramContext {
             df_ctx: SessionContext::new(),
             tables: HashMap::new(),
+            pins: HashMap::new(),
+            settings: Settings::default(),
+            interactive: false,
+        }
+    }
+
+    /// Pins a table to a snapshot, or unpins it with `None` or `TableVersion::Latest`
+    pub fn set_pin(&mut self, table_name: &str, version: Option<TableVersion>) {
+        match version {
+            Some(version) if version != TableVersion::Latest => {
+                self.pins.insert(table_name.to_string(), version);
+            }
+            _ => {
+                self.pins.remove(table_name);
+            }
+        }
+    }
+
+    /// Loads another version of an opened table, leaving the registered one as it is.
+    /// The copy keeps the storage options the table was opened with.
+    pub async fn load_snapshot(
+        &self,
+        table_name: &str,
+        version: &TableVersion,
+    ) -> Result<DeltaTable, String> {
+        let Some(table) = self.tables.get(table_name) else {
+            return Err(format!("table '{}' not found", table_name));
+        };
+        let mut table = table.as_ref().clone();
+        version.load(&mut table).await.map_err(|e| e.to_string())?;
+        Ok(table)
+    }
+
+    /// Loads the latest version of an opened table, even if it is pinned.
+    /// Writes have to start from it, or they would conflict with the newer commits.
+    pub async fn load_latest(&self, table_name: &str) -> Result<DeltaTable, String> {
+        self.load_snapshot(table_name, &TableVersion::Latest).await
+    }
+
+    /// Loads the latest version of an opened table to write to it.
+    /// Pinned tables are rejected, as they would not show the write.
+    pub async fn load_for_write(&self, table_name: &str) -> Result<DeltaTable, String> {
+        if let Some(version) = self.pins.get(table_name) {
+            return Err(format!(
+                "table '{}' is pinned to {}, use .checkout {} latest before writing to it",
+                table_name, version, table_name
+            ));
+        }
+        self.load_latest(table_name).await
+    }
+
+    /// Updates an opened table to its latest version, and re-registers it if it moved.
+    /// Returns the versions before and after the update
+    pub async fn update_table(
+        &mut self,
+        table_name: &str,
+    ) -> Result<(Option<i64>, Option<i64>), String> {
+        let Some(table) = self.tables.get(table_name) else {
+            return Err(format!("table '{}' not found", table_name));
+        };
+        let before = table.version();
+        let table = self.load_latest(table_name).await?;
+        let after = table.version();
+        if after != before {
+            self.refresh_table(table_name, table).await;
         }
+        Ok((before, after))
+    }
+
+    /// Names of every table registered in the DataFusion context, sorted
+    pub fn table_names(&self) -> Vec<String> {
+        let mut names = self
+            .df_ctx
+            .catalog("datafusion")
+            .and_then(|catalog| catalog.schema("public"))
+            .map(|schema| schema.table_names())
+            .unwrap_or_default();
+        names.sort();
+        names
     }
 
     pub async fn refresh_table(&mut self, table_name: &str, table: DeltaTable) {
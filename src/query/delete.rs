@@ -0,0 +1,195 @@
+use std::io::{BufRead, Write};
+
+use deltalake::{
+    DeltaOps,
+    datafusion::sql::sqlparser::ast::{Delete, FromTable, Statement},
+};
+
+use crate::{
+    program_context::ProgramContext,
+    utils::sql::{parse_statement, table_expr, table_qualifiers},
+};
+
+/// Runs a `DELETE` on an opened table with delta-rs, in a single commit.
+/// Deleting every row has to be confirmed, unless `force_delete` is set.
+pub async fn run_delete(
+    ctx: &mut ProgramContext,
+    table_name: &str,
+    sql: &str,
+) -> Result<(), String> {
+    let predicate = parse_delete(sql)?;
+
+    let table = ctx.load_for_write(table_name).await?;
+    let mut delete = DeltaOps::from(table).delete();
+    match predicate {
+        Some(predicate) => delete = delete.with_predicate(predicate),
+        None if ctx.settings.force_delete => {}
+        None if ctx.interactive => {
+            let question = format!("Delete every row of {}?", table_name);
+            if !confirm(&question, &mut std::io::stdin().lock())? {
+                println!("Nothing deleted");
+                return Ok(());
+            }
+        }
+        None => {
+            return Err(format!(
+                "DELETE without WHERE deletes every row of {}, use .set force_delete on to allow it",
+                table_name
+            ));
+        }
+    }
+    let (table, metrics) = delete.await.map_err(|e| e.to_string())?;
+    let version = table.version().unwrap_or_default();
+    ctx.refresh_table(table_name, table).await;
+
+    println!(
+        "Deleted {} rows, rewrote {} files, {} is now at version {}",
+        metrics.num_deleted_rows, metrics.num_removed_files, table_name, version
+    );
+    Ok(())
+}
+
+/// Returns the predicate of a `DELETE`, as SQL for delta-rs to parse again
+pub fn parse_delete(sql: &str) -> Result<Option<String>, String> {
+    let Statement::Delete(Delete {
+        from,
+        using,
+        selection,
+        ..
+    }) = parse_statement(sql)?
+    else {
+        return Err("expected a DELETE statement".to_string());
+    };
+    if using.is_some() {
+        return Err(
+            "DELETE ... USING is not supported on Delta tables, use MERGE INTO".to_string(),
+        );
+    }
+
+    let (FromTable::WithFromKeyword(tables) | FromTable::WithoutKeyword(tables)) = &from;
+    let qualifiers = tables
+        .first()
+        .map(|table| table_qualifiers(&table.relation))
+        .unwrap_or_default();
+    selection
+        .map(|selection| table_expr(selection, &qualifiers, "DELETE"))
+        .transpose()
+}
+
+/// Asks a yes or no question, defaulting to no
+fn confirm(question: &str, input: &mut impl BufRead) -> Result<bool, String> {
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush().map_err(|e| e.to_string())?;
+    let mut answer = String::new();
+    input.read_line(&mut answer).map_err(|e| e.to_string())?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::{
+        program_context::ProgramContext,
+        query::delete::{confirm, parse_delete},
+        runner::run_query,
+        utils::{
+            test_tables::{create_table, query_rows},
+            time_travel::TableVersion,
+        },
+    };
+
+    #[test]
+    fn test_parse_delete() {
+        assert_eq!(
+            parse_delete("DELETE FROM orders o WHERE o.status = 'lost' OR orders.id = 1"),
+            Ok(Some("status = 'lost' OR id = 1".to_string()))
+        );
+        assert_eq!(parse_delete("DELETE FROM orders"), Ok(None));
+        assert_eq!(
+            parse_delete("DELETE FROM orders WHERE id IN (SELECT id FROM lost)"),
+            Err(
+                "subqueries are not supported in DELETE on Delta tables, use MERGE INTO"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_confirm() {
+        for (answer, expected) in [
+            ("y\n", true),
+            (" YES \n", true),
+            ("\n", false),
+            ("no\n", false),
+            ("", false),
+        ] {
+            assert_eq!(
+                confirm("Delete?", &mut Cursor::new(answer)),
+                Ok(expected),
+                "{:?}",
+                answer
+            );
+        }
+    }
+
+    async fn orders(ctx: &mut ProgramContext) -> tempfile::TempDir {
+        let dir = create_table(ctx, "orders").await;
+        run_query(
+            ctx,
+            "INSERT INTO orders VALUES (1, 'pending'), (2, 'lost');",
+        )
+        .await
+        .unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_delete_where() {
+        let mut ctx = ProgramContext::new();
+        let _dir = orders(&mut ctx).await;
+
+        run_query(&mut ctx, "DELETE FROM orders o WHERE o.status = 'lost';")
+            .await
+            .unwrap();
+        assert_eq!(ctx.tables["orders"].version(), Some(2));
+        assert_eq!(
+            query_rows(&ctx, "SELECT * FROM orders").await,
+            vec!["1, pending"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_all() {
+        let mut ctx = ProgramContext::new();
+        let _dir = orders(&mut ctx).await;
+
+        // Scripts can't be asked for confirmation
+        let err = run_query(&mut ctx, "DELETE FROM orders;")
+            .await
+            .unwrap_err();
+        assert!(err.contains("force_delete"), "{}", err);
+        assert_eq!(ctx.load_latest("orders").await.unwrap().version(), Some(1));
+
+        ctx.settings.force_delete = true;
+        run_query(&mut ctx, "DELETE FROM orders;").await.unwrap();
+        assert_eq!(ctx.tables["orders"].version(), Some(2));
+        assert!(query_rows(&ctx, "SELECT * FROM orders").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_delete_pinned() {
+        let mut ctx = ProgramContext::new();
+        let _dir = orders(&mut ctx).await;
+        ctx.set_pin("orders", Some(TableVersion::Version(1)));
+
+        let err = run_query(&mut ctx, "DELETE FROM orders WHERE id = 1;")
+            .await
+            .unwrap_err();
+        assert!(err.contains("pinned"), "{}", err);
+        assert_eq!(ctx.load_latest("orders").await.unwrap().version(), Some(1));
+    }
+}
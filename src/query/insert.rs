@@ -0,0 +1,121 @@
+use deltalake::{
+    DeltaOps,
+    arrow::array::RecordBatch,
+    datafusion::{
+        dataframe::DataFrame,
+        logical_expr::{DmlStatement, WriteOp, dml::InsertOp},
+    },
+    protocol::SaveMode,
+};
+
+use crate::program_context::ProgramContext;
+
+/// Writes the rows of an `INSERT INTO` or `INSERT OVERWRITE` into an opened table, in a single commit
+pub async fn run_insert(ctx: &mut ProgramContext, dml: DmlStatement) -> Result<(), String> {
+    let table_name = dml.table_name.table().to_string();
+    let save_mode = match dml.op {
+        WriteOp::Insert(InsertOp::Append) => SaveMode::Append,
+        WriteOp::Insert(InsertOp::Overwrite) => SaveMode::Overwrite,
+        op => return Err(format!("{} is not supported on Delta tables", op)),
+    };
+
+    // DataFusion already cast the rows to the table schema
+    let input = DataFrame::new(ctx.df_ctx.state(), dml.input.as_ref().clone());
+    let batches: Vec<RecordBatch> = input.collect().await.map_err(|e| e.to_string())?;
+    let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+    let version = write_batches(ctx, &table_name, batches, save_mode).await?;
+
+    println!(
+        "Inserted {} rows, {} is now at version {}",
+        rows, table_name, version
+    );
+    Ok(())
+}
+
+/// Writes batches with the table schema into an opened table in a single commit,
+/// and moves the table to the new version, which is returned
+pub async fn write_batches(
+    ctx: &mut ProgramContext,
+    table_name: &str,
+    batches: Vec<RecordBatch>,
+    save_mode: SaveMode,
+) -> Result<i64, String> {
+    let table = ctx.load_for_write(table_name).await?;
+    let table = DeltaOps::from(table)
+        .write(batches)
+        .with_save_mode(save_mode)
+        .await
+        .map_err(|e| e.to_string())?;
+    let version = table.version().unwrap_or_default();
+    ctx.refresh_table(table_name, table).await;
+    Ok(version)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        program_context::ProgramContext,
+        runner::run_query,
+        utils::{
+            test_tables::{create_table, query_rows},
+            time_travel::TableVersion,
+        },
+    };
+
+    #[tokio::test]
+    async fn test_insert_new_version() {
+        let mut ctx = ProgramContext::new();
+        let _dir = create_table(&mut ctx, "orders").await;
+
+        run_query(
+            &mut ctx,
+            "INSERT INTO orders VALUES (1, 'pending'), (2, NULL);",
+        )
+        .await
+        .unwrap();
+        assert_eq!(ctx.tables["orders"].version(), Some(1));
+        assert_eq!(
+            query_rows(&ctx, "SELECT * FROM orders").await,
+            vec!["1, pending", "2, NULL"]
+        );
+
+        run_query(&mut ctx, "INSERT OVERWRITE orders VALUES (3, 'shipped');")
+            .await
+            .unwrap();
+        assert_eq!(ctx.tables["orders"].version(), Some(2));
+        assert_eq!(
+            query_rows(&ctx, "SELECT * FROM orders").await,
+            vec!["3, shipped"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_insert_pinned() {
+        let mut ctx = ProgramContext::new();
+        let _dir = create_table(&mut ctx, "orders").await;
+        ctx.set_pin("orders", Some(TableVersion::Version(0)));
+
+        let err = run_query(&mut ctx, "INSERT INTO orders VALUES (1, 'pending');")
+            .await
+            .unwrap_err();
+        assert!(err.contains("pinned"), "{}", err);
+        assert_eq!(ctx.load_latest("orders").await.unwrap().version(), Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_insert_cast() {
+        let mut ctx = ProgramContext::new();
+        let _dir = create_table(&mut ctx, "orders").await;
+
+        run_query(
+            &mut ctx,
+            "INSERT INTO orders SELECT CAST(7 AS TINYINT), 42 UNION ALL SELECT '8', 'lost';",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            query_rows(&ctx, "SELECT arrow_typeof(id), id, status FROM orders").await,
+            vec!["Int64, 7, 42", "Int64, 8, lost"]
+        );
+    }
+}
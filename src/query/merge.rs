@@ -0,0 +1,412 @@
+use deltalake::{
+    DeltaOps,
+    datafusion::{
+        common::TableReference,
+        dataframe::DataFrame,
+        datasource::TableProvider,
+        sql::sqlparser::ast::{
+            Assignment, MergeAction, MergeClauseKind, MergeInsertKind, Statement, TableFactor,
+        },
+    },
+    operations::merge::{DeleteBuilder, InsertBuilder, MergeBuilder, UpdateBuilder},
+};
+
+use crate::{
+    program_context::ProgramContext,
+    utils::sql::{assignment_column, parse_statement, table_qualifiers},
+};
+
+/// A `MERGE INTO` statement, with its expressions as SQL for delta-rs to parse again
+#[derive(Debug, PartialEq)]
+pub struct Merge {
+    pub table_name: String,
+    pub target_alias: String,
+    pub source: MergeSource,
+    pub source_alias: String,
+    pub on: String,
+    pub clauses: Vec<MergeClause>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MergeSource {
+    Table(String),
+    Query(String),
+}
+
+/// A `WHEN ... THEN ...` clause, with its optional `AND` predicate
+#[derive(Debug, PartialEq)]
+pub enum MergeClause {
+    MatchedUpdate(Option<String>, Vec<(String, String)>),
+    MatchedDelete(Option<String>),
+    /// Predicate, columns and values. No columns means all of them
+    NotMatchedInsert(Option<String>, Vec<String>, Vec<String>),
+    NotMatchedBySourceUpdate(Option<String>, Vec<(String, String)>),
+    NotMatchedBySourceDelete(Option<String>),
+}
+
+/// True for statements DataFusion can't plan, that have to go straight to `run_merge`
+pub fn is_merge(sql: &str) -> bool {
+    matches!(parse_statement(sql), Ok(Statement::Merge { .. }))
+}
+
+/// Runs a `MERGE INTO` an opened table with delta-rs, in a single commit.
+/// The source can be any table or query of the DataFusion context.
+pub async fn run_merge(ctx: &mut ProgramContext, sql: &str) -> Result<(), String> {
+    let mut merge = parse_merge(sql)?;
+    let Some(table) = ctx.tables.get(&merge.table_name) else {
+        return Err(format!("table '{}' not found", merge.table_name));
+    };
+
+    // INSERT without a column list sets every column, in the order of the table schema
+    let schema = TableProvider::schema(table.as_ref());
+    for clause in &mut merge.clauses {
+        if let MergeClause::NotMatchedInsert(_, columns, values) = clause {
+            if columns.is_empty() {
+                *columns = schema.fields().iter().map(|f| f.name().clone()).collect();
+            }
+            if columns.len() != values.len() {
+                return Err(format!(
+                    "INSERT in MERGE has {} columns but {} values",
+                    columns.len(),
+                    values.len()
+                ));
+            }
+        }
+    }
+
+    let source: DataFrame = match &merge.source {
+        MergeSource::Table(name) => ctx.df_ctx.table(name.as_str()).await,
+        MergeSource::Query(query) => ctx.df_ctx.sql(query).await,
+    }
+    .map_err(|e| e.to_string())?;
+
+    let table = ctx.load_for_write(&merge.table_name).await?;
+    let mut builder = DeltaOps::from(table)
+        .merge(source, merge.on)
+        .with_source_alias(&merge.source_alias)
+        .with_target_alias(&merge.target_alias);
+    for clause in merge.clauses {
+        builder = add_clause(builder, clause).map_err(|e| e.to_string())?;
+    }
+    let (table, metrics) = builder.await.map_err(|e| e.to_string())?;
+    let version = table.version().unwrap_or_default();
+    ctx.refresh_table(&merge.table_name, table).await;
+
+    println!(
+        "Inserted {} rows, updated {}, deleted {}, {} is now at version {}",
+        metrics.num_target_rows_inserted,
+        metrics.num_target_rows_updated,
+        metrics.num_target_rows_deleted,
+        merge.table_name,
+        version
+    );
+    Ok(())
+}
+
+fn add_clause(
+    builder: MergeBuilder,
+    clause: MergeClause,
+) -> Result<MergeBuilder, deltalake::DeltaTableError> {
+    match clause {
+        MergeClause::MatchedUpdate(predicate, assignments) => {
+            builder.when_matched_update(|update| set_updates(update, predicate, assignments))
+        }
+        MergeClause::MatchedDelete(predicate) => {
+            builder.when_matched_delete(|delete| set_delete(delete, predicate))
+        }
+        MergeClause::NotMatchedInsert(predicate, columns, values) => builder
+            .when_not_matched_insert(|insert| set_inserts(insert, predicate, columns, values)),
+        MergeClause::NotMatchedBySourceUpdate(predicate, assignments) => builder
+            .when_not_matched_by_source_update(|update| {
+                set_updates(update, predicate, assignments)
+            }),
+        MergeClause::NotMatchedBySourceDelete(predicate) => {
+            builder.when_not_matched_by_source_delete(|delete| set_delete(delete, predicate))
+        }
+    }
+}
+
+fn set_updates(
+    mut update: UpdateBuilder,
+    predicate: Option<String>,
+    assignments: Vec<(String, String)>,
+) -> UpdateBuilder {
+    if let Some(predicate) = predicate {
+        update = update.predicate(predicate);
+    }
+    for (column, value) in assignments {
+        update = update.update(column, value);
+    }
+    update
+}
+
+fn set_delete(delete: DeleteBuilder, predicate: Option<String>) -> DeleteBuilder {
+    match predicate {
+        Some(predicate) => delete.predicate(predicate),
+        None => delete,
+    }
+}
+
+fn set_inserts(
+    mut insert: InsertBuilder,
+    predicate: Option<String>,
+    columns: Vec<String>,
+    values: Vec<String>,
+) -> InsertBuilder {
+    if let Some(predicate) = predicate {
+        insert = insert.predicate(predicate);
+    }
+    for (column, value) in columns.into_iter().zip(values) {
+        insert = insert.set(column, value);
+    }
+    insert
+}
+
+/// Parses a `MERGE INTO` statement
+pub fn parse_merge(sql: &str) -> Result<Merge, String> {
+    let Statement::Merge {
+        table,
+        source,
+        on,
+        clauses,
+        ..
+    } = parse_statement(sql)?
+    else {
+        return Err("expected a MERGE statement".to_string());
+    };
+
+    let qualifiers = table_qualifiers(&table);
+    let TableFactor::Table { name, alias, .. } = table else {
+        return Err("MERGE INTO needs an opened table".to_string());
+    };
+    let table_name = TableReference::from(name.to_string().as_str())
+        .table()
+        .to_string();
+    let target_alias = alias.map_or(table_name.clone(), |alias| alias.name.value);
+
+    let (source, source_alias) = match source {
+        TableFactor::Table { name, alias, .. } => {
+            let name = name.to_string();
+            let alias = alias.map_or_else(
+                || TableReference::from(name.as_str()).table().to_string(),
+                |alias| alias.name.value,
+            );
+            (MergeSource::Table(name), alias)
+        }
+        TableFactor::Derived {
+            subquery,
+            alias: Some(alias),
+            ..
+        } => (MergeSource::Query(subquery.to_string()), alias.name.value),
+        TableFactor::Derived { alias: None, .. } => {
+            return Err("a subquery in USING needs an alias".to_string());
+        }
+        _ => return Err("USING needs a table or a subquery".to_string()),
+    };
+
+    let mut merge_clauses = Vec::new();
+    for clause in clauses {
+        let predicate = clause.predicate.map(|p| p.to_string());
+        let merge_clause = match (clause.clause_kind, clause.action) {
+            (MergeClauseKind::Matched, MergeAction::Update { assignments, .. }) => {
+                MergeClause::MatchedUpdate(predicate, parse_assignments(assignments, &qualifiers)?)
+            }
+            (MergeClauseKind::Matched, MergeAction::Delete) => {
+                MergeClause::MatchedDelete(predicate)
+            }
+            (
+                MergeClauseKind::NotMatched | MergeClauseKind::NotMatchedByTarget,
+                MergeAction::Insert(insert),
+            ) => {
+                let MergeInsertKind::Values(values) = insert.kind else {
+                    return Err("INSERT in MERGE needs VALUES".to_string());
+                };
+                let [row] = values.rows.as_slice() else {
+                    return Err("INSERT in MERGE needs a single row of VALUES".to_string());
+                };
+                let columns = insert.columns.iter().map(|c| c.value.clone()).collect();
+                let values = row.iter().map(|v| v.to_string()).collect();
+                MergeClause::NotMatchedInsert(predicate, columns, values)
+            }
+            (MergeClauseKind::NotMatchedBySource, MergeAction::Update { assignments, .. }) => {
+                MergeClause::NotMatchedBySourceUpdate(
+                    predicate,
+                    parse_assignments(assignments, &qualifiers)?,
+                )
+            }
+            (MergeClauseKind::NotMatchedBySource, MergeAction::Delete) => {
+                MergeClause::NotMatchedBySourceDelete(predicate)
+            }
+            (kind, action) => {
+                return Err(format!("WHEN {} THEN {} is not supported", kind, action));
+            }
+        };
+        merge_clauses.push(merge_clause);
+    }
+
+    Ok(Merge {
+        table_name,
+        target_alias,
+        source,
+        source_alias,
+        on: on.to_string(),
+        clauses: merge_clauses,
+    })
+}
+
+/// Columns can be qualified with the target table or its alias, delta-rs wants them without it
+fn parse_assignments(
+    assignments: Vec<Assignment>,
+    qualifiers: &[String],
+) -> Result<Vec<(String, String)>, String> {
+    assignments
+        .into_iter()
+        .map(|assignment| {
+            let column = assignment_column(&assignment.target, qualifiers)?;
+            Ok((column, assignment.value.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        program_context::ProgramContext,
+        query::merge::{Merge, MergeClause, MergeSource, is_merge, parse_merge},
+        runner::run_query,
+        utils::test_tables::{create_table, query_rows},
+    };
+
+    #[test]
+    fn test_parse_merge() {
+        let sql = "MERGE INTO orders t USING updates s ON t.id = s.id
+            WHEN MATCHED AND s.deleted THEN DELETE
+            WHEN MATCHED THEN UPDATE SET t.status = s.status, amount = s.amount * 2
+            WHEN NOT MATCHED THEN INSERT (id, status) VALUES (s.id, 'new')
+            WHEN NOT MATCHED BY SOURCE THEN DELETE;";
+        assert!(is_merge(sql));
+        assert_eq!(
+            parse_merge(sql),
+            Ok(Merge {
+                table_name: "orders".to_string(),
+                target_alias: "t".to_string(),
+                source: MergeSource::Table("updates".to_string()),
+                source_alias: "s".to_string(),
+                on: "t.id = s.id".to_string(),
+                clauses: vec![
+                    MergeClause::MatchedDelete(Some("s.deleted".to_string())),
+                    MergeClause::MatchedUpdate(
+                        None,
+                        vec![
+                            ("status".to_string(), "s.status".to_string()),
+                            ("amount".to_string(), "s.amount * 2".to_string()),
+                        ]
+                    ),
+                    MergeClause::NotMatchedInsert(
+                        None,
+                        vec!["id".to_string(), "status".to_string()],
+                        vec!["s.id".to_string(), "'new'".to_string()]
+                    ),
+                    MergeClause::NotMatchedBySourceDelete(None),
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_merge_query_source() {
+        let merge = parse_merge(
+            "MERGE INTO orders USING (SELECT * FROM staging) src ON orders.id = src.id
+            WHEN NOT MATCHED THEN INSERT VALUES (src.id, src.status)",
+        )
+        .unwrap();
+        assert_eq!(merge.target_alias, "orders");
+        assert_eq!(
+            merge.source,
+            MergeSource::Query("SELECT * FROM staging".to_string())
+        );
+        assert_eq!(merge.source_alias, "src");
+        assert_eq!(
+            merge.clauses,
+            vec![MergeClause::NotMatchedInsert(
+                None,
+                vec![],
+                vec!["src.id".to_string(), "src.status".to_string()]
+            )]
+        );
+
+        assert!(
+            parse_merge("MERGE INTO orders USING (SELECT 1) ON true WHEN MATCHED THEN DELETE")
+                .is_err()
+        );
+        assert!(!is_merge("SELECT * FROM orders"));
+    }
+
+    #[test]
+    fn test_is_merge() {
+        assert!(is_merge(
+            "-- upsert the staged rows\n\
+             MERGE INTO orders t USING staging s ON t.id = s.id WHEN MATCHED THEN DELETE"
+        ));
+        assert!(is_merge(
+            "/* nightly */ merge into orders t using staging s on t.id = s.id \
+             when not matched then insert (id) values (s.id);"
+        ));
+        assert!(!is_merge("SELECT 'MERGE' AS merge FROM orders"));
+        assert!(!is_merge("merge orders"));
+    }
+
+    #[tokio::test]
+    async fn test_merge_upsert() {
+        let mut ctx = ProgramContext::new();
+        let _orders = create_table(&mut ctx, "orders").await;
+        let _staging = create_table(&mut ctx, "staging").await;
+        run_query(
+            &mut ctx,
+            "INSERT INTO orders VALUES (1, 'pending'), (2, 'pending'), (3, 'lost');",
+        )
+        .await
+        .unwrap();
+        run_query(
+            &mut ctx,
+            "INSERT INTO staging VALUES (1, 'shipped'), (4, 'new');",
+        )
+        .await
+        .unwrap();
+
+        run_query(
+            &mut ctx,
+            "MERGE INTO orders t USING staging s ON t.id = s.id
+            WHEN MATCHED THEN UPDATE SET t.status = s.status
+            WHEN NOT MATCHED THEN INSERT VALUES (s.id, s.status)
+            WHEN NOT MATCHED BY SOURCE AND t.status = 'lost' THEN DELETE;",
+        )
+        .await
+        .unwrap();
+        assert_eq!(ctx.tables["orders"].version(), Some(2));
+        assert_eq!(
+            query_rows(&ctx, "SELECT * FROM orders").await,
+            vec!["1, shipped", "2, pending", "4, new"]
+        );
+    }
+
+    #[test]
+    fn test_parse_merge_qualified_assignments() {
+        let merge = parse_merge(
+            "MERGE INTO orders t USING staging s ON t.id = s.id
+            WHEN MATCHED THEN UPDATE SET orders.status = s.status, t.amount = 0, id = s.id",
+        )
+        .unwrap();
+        assert_eq!(
+            merge.clauses,
+            vec![MergeClause::MatchedUpdate(
+                None,
+                vec![
+                    ("status".to_string(), "s.status".to_string()),
+                    ("amount".to_string(), "0".to_string()),
+                    ("id".to_string(), "s.id".to_string()),
+                ]
+            )]
+        );
+    }
+}
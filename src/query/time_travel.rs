@@ -0,0 +1,558 @@
+use std::sync::Arc;
+
+use deltalake::{
+    DeltaOps, datafusion::datasource::TableProvider, delta_datafusion::DeltaCdfTableProvider,
+};
+
+use crate::{
+    program_context::ProgramContext,
+    utils::{
+        sql::quote_ident,
+        time_travel::{TableVersion, parse_timestamp},
+    },
+};
+
+/// Keywords that can follow a table in a FROM clause, and so are not an alias
+const NOT_ALIAS: &[&str] = &[
+    "CROSS",
+    "EXCEPT",
+    "FULL",
+    "GROUP",
+    "HAVING",
+    "INNER",
+    "INTERSECT",
+    "JOIN",
+    "LEFT",
+    "LIMIT",
+    "NATURAL",
+    "OFFSET",
+    "ON",
+    "ORDER",
+    "QUALIFY",
+    "RIGHT",
+    "UNION",
+    "USING",
+    "WHERE",
+    "WINDOW",
+];
+
+/// Keywords after which a table can go
+const BEFORE_TABLE: &[&str] = &["FROM", "JOIN", "USING"];
+
+/// Keywords that end a FROM clause
+const NOT_FROM: &[&str] = &[
+    "EXCEPT",
+    "GROUP",
+    "HAVING",
+    "INTERSECT",
+    "LIMIT",
+    "OFFSET",
+    "ORDER",
+    "QUALIFY",
+    "SELECT",
+    "SET",
+    "UNION",
+    "VALUES",
+    "WHEN",
+    "WHERE",
+    "WINDOW",
+];
+
+/// What is read from the history of a table
+#[derive(Debug, PartialEq)]
+pub enum SnapshotSource {
+    /// The table as it was at a version
+    Version(TableVersion),
+    /// The Change Data Feed between two versions, both included. Without an end, up to the latest one
+    Changes(i64, Option<i64>),
+}
+
+/// A reference in a query to a historical snapshot of an opened table
+#[derive(Debug, PartialEq)]
+pub struct SnapshotRef {
+    /// Name the snapshot is registered under while the query runs
+    pub temp_name: String,
+    pub table_name: String,
+    pub source: SnapshotSource,
+}
+
+/// A query with its time travel references replaced by temporary table names
+#[derive(Debug, PartialEq)]
+pub struct Rewrite {
+    pub sql: String,
+    pub snapshots: Vec<SnapshotRef>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Tok<'a> {
+    /// An identifier or keyword, with the name it resolves to
+    Word(&'a str, String),
+    Number(&'a str),
+    Str(String),
+    Punct(char),
+}
+
+struct Lexeme<'a> {
+    tok: Tok<'a>,
+    start: usize,
+    end: usize,
+}
+
+/// Replaces the time travel syntax in a query:
+/// - `orders VERSION AS OF 12` and `orders TIMESTAMP AS OF '2026-01-01'`
+/// - `delta_version('orders', 12)` and `delta_timestamp('orders', '2026-01-01')`
+/// - `table_changes('orders', 10, 12)`, with the end version being optional
+///
+/// Each one becomes a temporary table, aliased as the table name unless the query gives an alias.
+/// Only the places where a table can go are rewritten, anything else is left for DataFusion.
+pub fn rewrite_time_travel(sql: &str) -> Result<Rewrite, String> {
+    let lexemes = lex(sql);
+    let mut out = String::with_capacity(sql.len());
+    let mut snapshots = Vec::new();
+    let mut last = 0;
+    let mut i = 0;
+    // For each level of brackets, whether it is in a FROM clause
+    let mut in_from = vec![false];
+    let mut table_position = false;
+
+    while i < lexemes.len() {
+        let matched = if table_position {
+            match_time_travel(&lexemes[i..])?
+        } else {
+            None
+        };
+        let Some((table_name, alias, source, len)) = matched else {
+            table_position = precedes_table(&mut in_from, &lexemes[i].tok);
+            i += 1;
+            continue;
+        };
+
+        let temp_name = format!("__deltaq_snapshot_{}", snapshots.len());
+        let end = lexemes[i + len - 1].end;
+        out.push_str(&sql[last..lexemes[i].start]);
+        out.push_str(&temp_name);
+        if !has_alias(lexemes.get(i + len)) {
+            out.push_str(" AS ");
+            out.push_str(&alias);
+        }
+        last = end;
+
+        snapshots.push(SnapshotRef {
+            temp_name,
+            table_name,
+            source,
+        });
+        i += len;
+        table_position = false;
+    }
+
+    if snapshots.is_empty() {
+        return Ok(Rewrite {
+            sql: sql.to_string(),
+            snapshots,
+        });
+    }
+    out.push_str(&sql[last..]);
+    Ok(Rewrite {
+        sql: out,
+        snapshots,
+    })
+}
+
+/// Matches a time travel reference at the start of `lexemes`.
+/// Returns the table name, the alias to use for it, what to read, and the number of lexemes matched.
+fn match_time_travel(
+    lexemes: &[Lexeme],
+) -> Result<Option<(String, String, SnapshotSource, usize)>, String> {
+    let toks: Vec<&Tok> = lexemes.iter().take(8).map(|l| &l.tok).collect();
+    let is_word =
+        |tok: &Tok, word: &str| matches!(tok, Tok::Word(raw, _) if raw.eq_ignore_ascii_case(word));
+
+    match toks.as_slice() {
+        [Tok::Word(raw, name), kind, as_, of, value, ..]
+            if is_word(as_, "AS") && is_word(of, "OF") =>
+        {
+            let version = match (kind, value) {
+                (kind, Tok::Number(n)) if is_word(kind, "VERSION") => parse_version(n)?,
+                (kind, Tok::Str(s)) if is_word(kind, "TIMESTAMP") => {
+                    TableVersion::Timestamp(parse_timestamp(s)?)
+                }
+                _ => return Ok(None),
+            };
+            Ok(Some((
+                name.clone(),
+                raw.to_string(),
+                SnapshotSource::Version(version),
+                5,
+            )))
+        }
+        [
+            Tok::Word(_, function),
+            Tok::Punct('('),
+            Tok::Str(table_name),
+            Tok::Punct(','),
+            Tok::Number(from),
+            Tok::Punct(','),
+            Tok::Number(to),
+            Tok::Punct(')'),
+        ] if function == "table_changes" => {
+            let source = SnapshotSource::Changes(parse_number(from)?, Some(parse_number(to)?));
+            Ok(Some((
+                table_name.clone(),
+                quote_ident(table_name),
+                source,
+                8,
+            )))
+        }
+        [
+            Tok::Word(_, function),
+            Tok::Punct('('),
+            Tok::Str(table_name),
+            Tok::Punct(','),
+            value,
+            Tok::Punct(')'),
+            ..,
+        ] => {
+            let source = match (function.as_str(), value) {
+                ("delta_version", Tok::Number(n)) => SnapshotSource::Version(parse_version(n)?),
+                ("delta_timestamp", Tok::Str(s)) => {
+                    SnapshotSource::Version(TableVersion::Timestamp(parse_timestamp(s)?))
+                }
+                ("table_changes", Tok::Number(n)) => {
+                    SnapshotSource::Changes(parse_number(n)?, None)
+                }
+                ("delta_version" | "delta_timestamp" | "table_changes", _) => {
+                    return Err(format!("invalid arguments for {}", function));
+                }
+                _ => return Ok(None),
+            };
+            Ok(Some((
+                table_name.clone(),
+                quote_ident(table_name),
+                source,
+                6,
+            )))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn parse_number(n: &str) -> Result<i64, String> {
+    n.parse().map_err(|_| format!("invalid version '{}'", n))
+}
+
+fn parse_version(n: &str) -> Result<TableVersion, String> {
+    parse_number(n).map(TableVersion::Version)
+}
+
+/// Follows the clauses of a query through `tok`, and tells whether a table can go after it
+fn precedes_table(in_from: &mut Vec<bool>, tok: &Tok) -> bool {
+    match tok {
+        Tok::Punct('(') => {
+            in_from.push(false);
+            false
+        }
+        Tok::Punct(')') => {
+            if in_from.len() > 1 {
+                in_from.pop();
+            }
+            false
+        }
+        // Tables in a FROM clause are separated by commas
+        Tok::Punct(',') => in_from.last().copied().unwrap_or(false),
+        Tok::Word(raw, _) => {
+            let upper = raw.to_uppercase();
+            let from = if BEFORE_TABLE.contains(&upper.as_str()) {
+                true
+            } else if NOT_FROM.contains(&upper.as_str()) {
+                false
+            } else {
+                return false;
+            };
+            if let Some(last) = in_from.last_mut() {
+                *last = from;
+            }
+            from
+        }
+        _ => false,
+    }
+}
+
+fn has_alias(next: Option<&Lexeme>) -> bool {
+    match next.map(|l| &l.tok) {
+        Some(Tok::Word(raw, _)) => !NOT_ALIAS.contains(&raw.to_uppercase().as_str()),
+        _ => false,
+    }
+}
+
+/// Splits SQL into words, numbers, strings and punctuation, skipping whitespace and comments
+fn lex(sql: &str) -> Vec<Lexeme<'_>> {
+    let chars: Vec<(usize, char)> = sql.char_indices().collect();
+    let pos = |i: usize| chars.get(i).map(|(p, _)| *p).unwrap_or(sql.len());
+    let mut lexemes = Vec::new();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        let next = chars.get(i + 1).map(|(_, c)| *c);
+        let mut j = i + 1;
+        let tok = match c {
+            c if c.is_whitespace() => None,
+            '-' if next == Some('-') => {
+                while j < chars.len() && chars[j].1 != '\n' {
+                    j += 1;
+                }
+                None
+            }
+            '/' if next == Some('*') => {
+                j += 1;
+                while j < chars.len()
+                    && !(chars[j].1 == '*' && chars.get(j + 1).is_some_and(|(_, c)| *c == '/'))
+                {
+                    j += 1;
+                }
+                j = (j + 2).min(chars.len());
+                None
+            }
+            '\'' => {
+                let mut value = String::new();
+                while j < chars.len() {
+                    if chars[j].1 == '\'' {
+                        // A doubled quote is an escaped quote, not the end of the string
+                        if chars.get(j + 1).is_some_and(|(_, c)| *c == '\'') {
+                            value.push('\'');
+                            j += 2;
+                            continue;
+                        }
+                        j += 1;
+                        break;
+                    }
+                    value.push(chars[j].1);
+                    j += 1;
+                }
+                Some(Tok::Str(value))
+            }
+            '"' => {
+                while j < chars.len() && chars[j].1 != '"' {
+                    j += 1;
+                }
+                j = (j + 1).min(chars.len());
+                let raw = &sql[start..pos(j)];
+                let name = raw
+                    .trim_start_matches('"')
+                    .trim_end_matches('"')
+                    .to_string();
+                Some(Tok::Word(raw, name))
+            }
+            c if c.is_ascii_digit() => {
+                while j < chars.len() && (chars[j].1.is_ascii_alphanumeric() || chars[j].1 == '.') {
+                    j += 1;
+                }
+                Some(Tok::Number(&sql[start..pos(j)]))
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                while j < chars.len() && (chars[j].1.is_alphanumeric() || chars[j].1 == '_') {
+                    j += 1;
+                }
+                let raw = &sql[start..pos(j)];
+                // Unquoted identifiers are case insensitive, like in DataFusion
+                Some(Tok::Word(raw, raw.to_lowercase()))
+            }
+            c => Some(Tok::Punct(c)),
+        };
+        if let Some(tok) = tok {
+            lexemes.push(Lexeme {
+                tok,
+                start,
+                end: pos(j),
+            });
+        }
+        i = j;
+    }
+
+    lexemes
+}
+
+/// Loads and registers every snapshot a query refers to.
+/// Returns the names registered, which must be dropped with `deregister_snapshots`.
+pub async fn register_snapshots(
+    ctx: &ProgramContext,
+    snapshots: &[SnapshotRef],
+) -> Result<Vec<String>, String> {
+    let mut registered = Vec::new();
+    for snapshot in snapshots {
+        let provider = match load_provider(ctx, snapshot).await {
+            Ok(provider) => provider,
+            Err(e) => {
+                deregister_snapshots(ctx, &registered);
+                return Err(e);
+            }
+        };
+        if let Err(e) = ctx
+            .df_ctx
+            .register_table(snapshot.temp_name.as_str(), provider)
+        {
+            deregister_snapshots(ctx, &registered);
+            return Err(e.to_string());
+        }
+        registered.push(snapshot.temp_name.clone());
+    }
+    Ok(registered)
+}
+
+async fn load_provider(
+    ctx: &ProgramContext,
+    snapshot: &SnapshotRef,
+) -> Result<Arc<dyn TableProvider>, String> {
+    match &snapshot.source {
+        SnapshotSource::Version(version) => {
+            let table = ctx.load_snapshot(&snapshot.table_name, version).await?;
+            Ok(Arc::new(table))
+        }
+        SnapshotSource::Changes(from, to) => {
+            let table = ctx
+                .load_snapshot(&snapshot.table_name, &TableVersion::Latest)
+                .await?;
+            let mut cdf = DeltaOps::from(table)
+                .load_cdf()
+                .with_starting_version(*from);
+            if let Some(to) = to {
+                cdf = cdf.with_ending_version(*to);
+            }
+            let provider = DeltaCdfTableProvider::try_new(cdf).map_err(|e| e.to_string())?;
+            Ok(Arc::new(provider))
+        }
+    }
+}
+
+pub fn deregister_snapshots(ctx: &ProgramContext, names: &[String]) {
+    for name in names {
+        let _ = ctx.df_ctx.deregister_table(name.as_str());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{TimeZone, Utc};
+
+    use crate::{
+        query::time_travel::{SnapshotRef, SnapshotSource, rewrite_time_travel},
+        utils::time_travel::TableVersion,
+    };
+
+    #[test]
+    fn test_rewrite_version_as_of() {
+        let rewrite =
+            rewrite_time_travel("SELECT * FROM Orders VERSION AS OF 12 WHERE id = 1").unwrap();
+        assert_eq!(
+            rewrite.sql,
+            "SELECT * FROM __deltaq_snapshot_0 AS Orders WHERE id = 1"
+        );
+        assert_eq!(
+            rewrite.snapshots,
+            vec![SnapshotRef {
+                temp_name: "__deltaq_snapshot_0".into(),
+                table_name: "orders".into(),
+                source: SnapshotSource::Version(TableVersion::Version(12)),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_rewrite_timestamp_as_of_with_alias() {
+        let rewrite = rewrite_time_travel(
+            "SELECT * FROM orders o JOIN orders TIMESTAMP AS OF '2026-01-01' old ON o.id = old.id",
+        )
+        .unwrap();
+        assert_eq!(
+            rewrite.sql,
+            "SELECT * FROM orders o JOIN __deltaq_snapshot_0 old ON o.id = old.id"
+        );
+        assert_eq!(
+            rewrite.snapshots[0].source,
+            SnapshotSource::Version(TableVersion::Timestamp(
+                Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_rewrite_functions() {
+        let rewrite = rewrite_time_travel(
+            "SELECT * FROM delta_version('Orders', 3) a, delta_timestamp('orders', '2026-01-01T00:00:00Z')",
+        )
+        .unwrap();
+        assert_eq!(
+            rewrite.sql,
+            "SELECT * FROM __deltaq_snapshot_0 a, __deltaq_snapshot_1 AS \"orders\""
+        );
+        assert_eq!(rewrite.snapshots[0].table_name, "Orders");
+        assert_eq!(rewrite.snapshots[1].table_name, "orders");
+    }
+
+    #[test]
+    fn test_rewrite_table_changes() {
+        let rewrite = rewrite_time_travel(
+            "SELECT * FROM table_changes('orders', 3, 5) c JOIN table_changes('users', 7) u ON c.id = u.id",
+        )
+        .unwrap();
+        assert_eq!(
+            rewrite.sql,
+            "SELECT * FROM __deltaq_snapshot_0 c JOIN __deltaq_snapshot_1 u ON c.id = u.id"
+        );
+        assert_eq!(
+            rewrite.snapshots[0].source,
+            SnapshotSource::Changes(3, Some(5))
+        );
+        assert_eq!(
+            rewrite.snapshots[1].source,
+            SnapshotSource::Changes(7, None)
+        );
+    }
+
+    #[test]
+    fn test_rewrite_ignores_strings_and_comments() {
+        let sql = "SELECT 'orders VERSION AS OF 1' -- orders VERSION AS OF 2\nFROM orders;";
+        let rewrite = rewrite_time_travel(sql).unwrap();
+        assert_eq!(rewrite.sql, sql);
+        assert!(rewrite.snapshots.is_empty());
+    }
+
+    #[test]
+    fn test_rewrite_only_tables() {
+        let sql = "SELECT delta_version('orders', 3), table_changes('orders', 1) AS c FROM orders;";
+        let rewrite = rewrite_time_travel(sql).unwrap();
+        assert_eq!(rewrite.sql, sql);
+        assert!(rewrite.snapshots.is_empty());
+
+        let rewrite = rewrite_time_travel(
+            "SELECT * FROM users WHERE id IN (SELECT user_id FROM orders VERSION AS OF 2), \
+             orders VERSION AS OF 3",
+        )
+        .unwrap();
+        assert_eq!(
+            rewrite.sql,
+            "SELECT * FROM users WHERE id IN (SELECT user_id FROM __deltaq_snapshot_0 AS orders), \
+             orders VERSION AS OF 3"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_merge_source() {
+        let rewrite = rewrite_time_travel(
+            "MERGE INTO orders t USING orders VERSION AS OF 4 s ON t.id = s.id \
+             WHEN NOT MATCHED THEN INSERT *",
+        )
+        .unwrap();
+        assert_eq!(
+            rewrite.sql,
+            "MERGE INTO orders t USING __deltaq_snapshot_0 s ON t.id = s.id \
+             WHEN NOT MATCHED THEN INSERT *"
+        );
+    }
+
+    #[test]
+    fn test_rewrite_invalid_timestamp() {
+        assert!(rewrite_time_travel("SELECT * FROM orders TIMESTAMP AS OF 'now'").is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use deltalake::{DeltaOps, datafusion::sql::sqlparser::ast::Statement};
+
+use crate::{
+    program_context::ProgramContext,
+    utils::sql::{assignment_column, parse_statement, table_expr, table_qualifiers},
+};
+
+/// An `UPDATE` statement, with its expressions as SQL for delta-rs to parse again
+#[derive(Debug, PartialEq)]
+pub struct Update {
+    pub predicate: Option<String>,
+    pub assignments: Vec<(String, String)>,
+}
+
+/// Runs an `UPDATE` on an opened table with delta-rs, in a single commit
+pub async fn run_update(
+    ctx: &mut ProgramContext,
+    table_name: &str,
+    sql: &str,
+) -> Result<(), String> {
+    let parsed = parse_update(sql)?;
+
+    let table = ctx.load_for_write(table_name).await?;
+    let mut update = DeltaOps::from(table).update();
+    if let Some(predicate) = parsed.predicate {
+        update = update.with_predicate(predicate);
+    }
+    for (column, value) in parsed.assignments {
+        update = update.with_update(column, value);
+    }
+    let (table, metrics) = update.await.map_err(|e| e.to_string())?;
+    let version = table.version().unwrap_or_default();
+    ctx.refresh_table(table_name, table).await;
+
+    println!(
+        "Updated {} rows, {} is now at version {}",
+        metrics.num_updated_rows, table_name, version
+    );
+    Ok(())
+}
+
+pub fn parse_update(sql: &str) -> Result<Update, String> {
+    let Statement::Update {
+        table,
+        assignments,
+        from,
+        selection,
+        ..
+    } = parse_statement(sql)?
+    else {
+        return Err("expected an UPDATE statement".to_string());
+    };
+    if from.is_some() {
+        return Err("UPDATE ... FROM is not supported on Delta tables, use MERGE INTO".to_string());
+    }
+
+    let qualifiers = table_qualifiers(&table.relation);
+    let predicate = selection
+        .map(|selection| table_expr(selection, &qualifiers, "UPDATE"))
+        .transpose()?;
+    let assignments = assignments
+        .into_iter()
+        .map(|assignment| {
+            let column = assignment_column(&assignment.target, &qualifiers)?;
+            let value = table_expr(assignment.value, &qualifiers, "UPDATE")?;
+            Ok((column, value))
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(Update {
+        predicate,
+        assignments,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        program_context::ProgramContext,
+        query::update::{Update, parse_update},
+        runner::run_query,
+        utils::test_tables::{create_table, query_rows},
+    };
+
+    #[test]
+    fn test_parse_update() {
+        assert_eq!(
+            parse_update(
+                "UPDATE orders o SET o.status = upper(o.status), amount = o.amount + 1 \
+                 WHERE o.id = 2 AND orders.amount > 0"
+            ),
+            Ok(Update {
+                predicate: Some("id = 2 AND amount > 0".to_string()),
+                assignments: vec![
+                    ("status".to_string(), "upper(status)".to_string()),
+                    ("amount".to_string(), "amount + 1".to_string()),
+                ],
+            })
+        );
+        assert_eq!(
+            parse_update("UPDATE orders SET orders.status = 'lost'"),
+            Ok(Update {
+                predicate: None,
+                assignments: vec![("status".to_string(), "'lost'".to_string())],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_update_subqueries() {
+        let error = "subqueries are not supported in UPDATE on Delta tables, use MERGE INTO";
+        for sql in [
+            "UPDATE orders SET status = 'lost' WHERE id IN (SELECT id FROM lost)",
+            "UPDATE orders SET status = 'lost' WHERE EXISTS (SELECT 1 FROM lost)",
+            "UPDATE orders SET status = (SELECT max(status) FROM lost)",
+        ] {
+            assert_eq!(parse_update(sql), Err(error.to_string()), "{}", sql);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_with_alias() {
+        let mut ctx = ProgramContext::new();
+        let _dir = create_table(&mut ctx, "orders").await;
+        run_query(
+            &mut ctx,
+            "INSERT INTO orders VALUES (1, 'pending'), (2, 'pending');",
+        )
+        .await
+        .unwrap();
+
+        run_query(
+            &mut ctx,
+            "UPDATE orders o SET o.status = 'shipped' WHERE o.id = 2;",
+        )
+        .await
+        .unwrap();
+        assert_eq!(ctx.tables["orders"].version(), Some(2));
+        assert_eq!(
+            query_rows(&ctx, "SELECT * FROM orders").await,
+            vec!["1, pending", "2, shipped"]
+        );
+    }
+}
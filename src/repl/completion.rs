@@ -0,0 +1,183 @@
+use deltalake::arrow::datatypes::{DataType, Fields};
+use rustyline::completion::Pair;
+
+use crate::{commands::definitions::command_definitions, storage::profiles::load_profiles};
+
+#[rustfmt::skip]
+pub const SQL_KEYWORDS: &[&str] = &[
+    "ALL", "AND", "AS", "ASC", "BETWEEN", "BY", "CASE", "CAST", "COUNT", "CREATE", "CROSS",
+    "DELETE", "DESC", "DISTINCT", "ELSE", "END", "EXCEPT", "EXISTS", "EXPLAIN", "FALSE",
+    "FROM", "FULL", "GROUP", "HAVING", "IN", "INNER", "INSERT", "INTERSECT", "INTO", "IS",
+    "JOIN", "LEFT", "LIKE", "LIMIT", "MATCHED", "MERGE", "NOT", "NULL", "OF", "OFFSET", "ON",
+    "OR", "ORDER", "OUTER", "OVER", "PARTITION", "RIGHT", "SELECT", "SET", "SHOW", "TABLE",
+    "THEN", "TIMESTAMP", "TRUE", "UNION", "UPDATE", "USING", "VALUES", "VERSION", "VIEW",
+    "WHEN", "WHERE", "WITH",
+];
+
+/// Everything the completer needs to know about the current session
+#[derive(Default)]
+pub struct CompletionData {
+    pub tables: Vec<String>,
+    /// Column names, with nested struct fields as dotted paths
+    pub columns: Vec<String>,
+}
+
+impl CompletionData {
+    pub fn add_columns(&mut self, fields: &Fields) {
+        add_columns(&mut self.columns, "", fields);
+        self.columns.sort();
+        self.columns.dedup();
+    }
+}
+
+fn add_columns(columns: &mut Vec<String>, prefix: &str, fields: &Fields) {
+    for field in fields {
+        let path = format!("{}{}", prefix, field.name());
+        if let DataType::Struct(children) = field.data_type() {
+            add_columns(columns, &format!("{}.", path), children);
+        }
+        columns.push(path);
+    }
+}
+
+/// Returns the position where the completed word starts, and the candidates for it
+pub fn complete(data: &CompletionData, line: &str, pos: usize) -> (usize, Vec<Pair>) {
+    let start = line[..pos]
+        .char_indices()
+        .rev()
+        .find(|(_, c)| c.is_whitespace() || "(),;=<>'\"".contains(*c))
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(0);
+    let word = &line[start..pos];
+
+    let candidates: Vec<String> = if line.trim_start().starts_with('.') {
+        complete_command(data, line, start, word)
+    } else {
+        complete_sql(data, word)
+    };
+
+    let mut pairs: Vec<Pair> = candidates
+        .into_iter()
+        .filter(|c| c.starts_with(word) && c != word)
+        .map(|c| Pair {
+            display: c.clone(),
+            replacement: c,
+        })
+        .collect();
+    pairs.sort_by(|a, b| a.display.cmp(&b.display));
+    pairs.dedup_by(|a, b| a.display == b.display);
+    (start, pairs)
+}
+
+fn complete_command(data: &CompletionData, line: &str, start: usize, word: &str) -> Vec<String> {
+    let mut words = line[..start].split_whitespace();
+    let Some(command_name) = words.next() else {
+        return command_definitions()
+            .iter()
+            .map(|c| format!(".{}", c.get_name()))
+            .collect();
+    };
+    let previous = words.last().unwrap_or(command_name);
+
+    let definition = command_definitions()
+        .into_iter()
+        .find(|c| format!(".{}", c.get_name()) == command_name);
+
+    if word.starts_with('-') {
+        let Some(definition) = definition else {
+            return Vec::new();
+        };
+        let mut flags: Vec<String> = definition
+            .get_arguments()
+            .filter_map(|a| a.get_long())
+            .map(|long| format!("--{}", long))
+            .collect();
+        flags.push("--help".into());
+        flags
+    } else if previous == "--profile" {
+        load_profiles()
+            .map(|profiles| profiles.into_keys().collect())
+            .unwrap_or_default()
+    } else {
+        data.tables.clone()
+    }
+}
+
+fn complete_sql(data: &CompletionData, word: &str) -> Vec<String> {
+    let lowercase = word.chars().any(|c| c.is_lowercase());
+    SQL_KEYWORDS
+        .iter()
+        .map(|k| {
+            if lowercase {
+                k.to_lowercase()
+            } else {
+                k.to_string()
+            }
+        })
+        .chain(data.tables.iter().cloned())
+        .chain(data.columns.iter().cloned())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use deltalake::arrow::datatypes::{DataType, Field, Fields};
+
+    use crate::repl::completion::{CompletionData, complete};
+
+    fn data() -> CompletionData {
+        let mut data = CompletionData {
+            tables: vec!["orders".into(), "users".into()],
+            columns: Vec::new(),
+        };
+        data.add_columns(&Fields::from(vec![
+            Field::new("order_id", DataType::Int64, false),
+            Field::new(
+                "customer",
+                DataType::Struct(Fields::from(vec![Field::new("name", DataType::Utf8, true)])),
+                true,
+            ),
+        ]));
+        data
+    }
+
+    fn replacements(line: &str) -> (usize, Vec<String>) {
+        let (start, pairs) = complete(&data(), line, line.len());
+        (start, pairs.into_iter().map(|p| p.replacement).collect())
+    }
+
+    #[test]
+    fn test_complete_command_name() {
+        assert_eq!(replacements(".sc"), (0, vec![".schema".to_string()]));
+    }
+
+    #[test]
+    fn test_complete_command_flag() {
+        assert_eq!(
+            replacements(".create --sc"),
+            (8, vec!["--schema".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_complete_table_name() {
+        assert_eq!(replacements(".schema or"), (8, vec!["orders".to_string()]));
+    }
+
+    #[test]
+    fn test_complete_sql() {
+        assert_eq!(
+            replacements("select cust"),
+            (7, vec!["customer".to_string(), "customer.name".to_string()])
+        );
+        assert_eq!(replacements("SELECT * FR"), (9, vec!["FROM".to_string()]));
+    }
+
+    #[test]
+    fn test_complete_after_multi_byte_whitespace() {
+        assert_eq!(
+            replacements("SELECT\u{a0}cust"),
+            (8, vec!["customer".to_string(), "customer.name".to_string()])
+        );
+    }
+}
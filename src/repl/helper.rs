@@ -0,0 +1,107 @@
+use std::{
+    borrow::Cow::{self, Owned},
+    cell::Cell,
+};
+
+use rustyline::{
+    Context, Helper,
+    completion::{Completer, Pair},
+    highlight::{CmdKind, Highlighter},
+    hint::Hinter,
+    validate::{ValidationContext, ValidationResult, Validator},
+};
+
+use deltalake::datafusion::datasource::TableProvider;
+
+use crate::program_context::ProgramContext;
+
+use super::{
+    completion::{CompletionData, complete},
+    highlight::{check_brackets, highlight},
+};
+
+/// rustyline helper for the REPL
+#[derive(Default)]
+pub struct ReplHelper {
+    completion: CompletionData,
+    /// SQL lines entered so far for a statement that isn't finished yet
+    pending_sql: String,
+    /// False on the final refresh of a line, so brackets are not left highlighted
+    highlight_brackets: Cell<bool>,
+}
+
+impl ReplHelper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Refreshes the table and column names offered by the completer
+    pub fn update(&mut self, ctx: &ProgramContext) {
+        let mut completion = CompletionData {
+            tables: ctx.table_names(),
+            columns: Vec::new(),
+        };
+        for table in ctx.tables.values() {
+            completion.add_columns(TableProvider::schema(table.as_ref()).fields());
+        }
+        self.completion = completion;
+    }
+
+    pub fn set_pending_sql(&mut self, sql: &str) {
+        self.pending_sql = sql.to_string();
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        Ok(complete(&self.completion, line, pos))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight<'l>(&self, line: &'l str, pos: usize) -> Cow<'l, str> {
+        let cursor = self.highlight_brackets.get().then_some(pos);
+        Owned(highlight(line, cursor))
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize, kind: CmdKind) -> bool {
+        self.highlight_brackets.set(kind != CmdKind::ForcedRefresh);
+        true
+    }
+}
+
+impl Validator for ReplHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        let input = ctx.input();
+        // SQL is only checked once the statement is complete, as brackets may be closed on a later line
+        let result = if input.trim_start().starts_with('.') {
+            check_brackets(input)
+        } else if input.trim_end().ends_with(';') {
+            if self.pending_sql.is_empty() {
+                check_brackets(input)
+            } else {
+                check_brackets(&format!("{}\n{}", self.pending_sql, input))
+            }
+        } else {
+            Ok(())
+        };
+
+        match result {
+            Ok(()) => Ok(ValidationResult::Valid(None)),
+            Err(e) => Ok(ValidationResult::Invalid(Some(format!(" - {}", e)))),
+        }
+    }
+}
+
+impl Helper for ReplHelper {}
@@ -0,0 +1,351 @@
+use crate::schema::{
+    parser::TYPE_NAMES,
+    tokenizer::{Token, tokenize},
+};
+
+use super::completion::SQL_KEYWORDS;
+
+const RESET: &str = "\x1b[0m";
+const COMMAND: &str = "\x1b[1m";
+const KEYWORD: &str = "\x1b[1;34m";
+const STRING: &str = "\x1b[32m";
+const NUMBER: &str = "\x1b[33m";
+const COMMENT: &str = "\x1b[2m";
+const TYPE: &str = "\x1b[36m";
+const NULLABLE: &str = "\x1b[33m";
+const MATCHING_BRACKET: &str = "\x1b[1;4m";
+const UNMATCHED_BRACKET: &str = "\x1b[1;31m";
+
+/// A styled byte range of the line
+struct Span {
+    start: usize,
+    end: usize,
+    style: &'static str,
+}
+
+/// A bracket found outside of strings and comments
+#[derive(Debug, Clone, Copy)]
+struct Bracket {
+    pos: usize,
+    c: char,
+}
+
+impl Bracket {
+    fn is_open(&self) -> bool {
+        matches!(self.c, '(' | '<')
+    }
+
+    fn closes(&self, open: &Bracket) -> bool {
+        matches!((open.c, self.c), ('(', ')') | ('<', '>'))
+    }
+}
+
+/// Highlights a line of input. When `cursor` is on or right after a bracket,
+/// the bracket and its matching pair are highlighted too.
+pub fn highlight(line: &str, cursor: Option<usize>) -> String {
+    let (mut spans, brackets) = scan(line);
+    let (pairs, unmatched) = match_brackets(&brackets);
+
+    for b in &unmatched {
+        spans.push(Span {
+            start: b.pos,
+            end: b.pos + 1,
+            style: UNMATCHED_BRACKET,
+        });
+    }
+    if let Some(cursor) = cursor {
+        let pair = pairs.iter().find(|(open, close)| {
+            [open.pos, close.pos]
+                .iter()
+                .any(|&p| p == cursor || p + 1 == cursor)
+        });
+        if let Some((open, close)) = pair {
+            for b in [open, close] {
+                spans.push(Span {
+                    start: b.pos,
+                    end: b.pos + 1,
+                    style: MATCHING_BRACKET,
+                });
+            }
+        }
+    }
+    spans.sort_by_key(|s| s.start);
+
+    let mut out = String::with_capacity(line.len() * 2);
+    let mut last = 0;
+    for span in spans {
+        out.push_str(&line[last..span.start]);
+        out.push_str(span.style);
+        out.push_str(&line[span.start..span.end]);
+        out.push_str(RESET);
+        last = span.end;
+    }
+    out.push_str(&line[last..]);
+    out
+}
+
+/// Checks that every bracket in the input is balanced
+pub fn check_brackets(input: &str) -> Result<(), String> {
+    let (_, brackets) = scan(input);
+    let (_, unmatched) = match_brackets(&brackets);
+    match unmatched.first() {
+        Some(b) if b.is_open() => Err(format!("unclosed '{}'", b.c)),
+        Some(b) => Err(format!("unmatched '{}'", b.c)),
+        None => Ok(()),
+    }
+}
+
+fn scan(input: &str) -> (Vec<Span>, Vec<Bracket>) {
+    if input.trim_start().starts_with('.') {
+        scan_command(input)
+    } else {
+        scan_sql(input)
+    }
+}
+
+/// Pairs the brackets up, returning the pairs and the brackets left without one
+fn match_brackets(brackets: &[Bracket]) -> (Vec<(Bracket, Bracket)>, Vec<Bracket>) {
+    let mut pairs = Vec::new();
+    let mut unmatched = Vec::new();
+    let mut stack: Vec<Bracket> = Vec::new();
+
+    for b in brackets {
+        if b.is_open() {
+            stack.push(*b);
+        } else if stack.last().is_some_and(|open| b.closes(open)) {
+            pairs.push((stack.pop().unwrap(), *b));
+        } else {
+            unmatched.push(*b);
+        }
+    }
+    unmatched.extend(stack);
+    unmatched.sort_by_key(|b| b.pos);
+    (pairs, unmatched)
+}
+
+fn scan_sql(input: &str) -> (Vec<Span>, Vec<Bracket>) {
+    let mut spans = Vec::new();
+    let mut brackets = Vec::new();
+    let chars: Vec<(usize, char)> = input.char_indices().collect();
+    let end_of = |i: usize| chars.get(i).map(|(pos, _)| *pos).unwrap_or(input.len());
+
+    let mut i = 0;
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        let next = chars.get(i + 1).map(|(_, c)| *c);
+        let mut j = i + 1;
+        match c {
+            '\'' => {
+                while j < chars.len() {
+                    if chars[j].1 == '\'' {
+                        // A doubled quote is an escaped quote, not the end of the string
+                        if chars.get(j + 1).is_some_and(|(_, c)| *c == '\'') {
+                            j += 2;
+                            continue;
+                        }
+                        j += 1;
+                        break;
+                    }
+                    j += 1;
+                }
+                spans.push(Span {
+                    start,
+                    end: end_of(j),
+                    style: STRING,
+                });
+            }
+            '"' => {
+                while j < chars.len() && chars[j].1 != '"' {
+                    j += 1;
+                }
+                j = (j + 1).min(chars.len());
+            }
+            '-' if next == Some('-') => {
+                while j < chars.len() && chars[j].1 != '\n' {
+                    j += 1;
+                }
+                spans.push(Span {
+                    start,
+                    end: end_of(j),
+                    style: COMMENT,
+                });
+            }
+            '/' if next == Some('*') => {
+                j += 1;
+                while j < chars.len()
+                    && !(chars[j].1 == '*' && chars.get(j + 1).is_some_and(|(_, c)| *c == '/'))
+                {
+                    j += 1;
+                }
+                j = (j + 2).min(chars.len());
+                spans.push(Span {
+                    start,
+                    end: end_of(j),
+                    style: COMMENT,
+                });
+            }
+            '(' | ')' => brackets.push(Bracket { pos: start, c }),
+            c if c.is_ascii_digit() => {
+                while j < chars.len() && (chars[j].1.is_ascii_alphanumeric() || chars[j].1 == '.') {
+                    j += 1;
+                }
+                spans.push(Span {
+                    start,
+                    end: end_of(j),
+                    style: NUMBER,
+                });
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                while j < chars.len() && (chars[j].1.is_alphanumeric() || chars[j].1 == '_') {
+                    j += 1;
+                }
+                let word = &input[start..end_of(j)];
+                if SQL_KEYWORDS.contains(&word.to_uppercase().as_str()) {
+                    spans.push(Span {
+                        start,
+                        end: end_of(j),
+                        style: KEYWORD,
+                    });
+                }
+            }
+            _ => {}
+        }
+        i = j;
+    }
+
+    (spans, brackets)
+}
+
+fn scan_command(input: &str) -> (Vec<Span>, Vec<Bracket>) {
+    let mut spans = Vec::new();
+    let mut brackets = Vec::new();
+
+    let command_start = input.len() - input.trim_start().len();
+    let command_end = input[command_start..]
+        .find(char::is_whitespace)
+        .map(|i| command_start + i)
+        .unwrap_or(input.len());
+    spans.push(Span {
+        start: command_start,
+        end: command_end,
+        style: COMMAND,
+    });
+
+    if &input[command_start..command_end] == ".create"
+        && let Some((start, end)) = find_flag_value(input, "--schema")
+    {
+        scan_schema(&input[start..end], start, &mut spans, &mut brackets);
+    }
+
+    (spans, brackets)
+}
+
+/// Highlights a schema with the same tokenizer used by the schema parser
+fn scan_schema(schema: &str, offset: usize, spans: &mut Vec<Span>, brackets: &mut Vec<Bracket>) {
+    // The tokenizer indexes by char, the spans by byte
+    let byte_pos: Vec<usize> = schema
+        .char_indices()
+        .map(|(i, _)| offset + i)
+        .chain([offset + schema.len()])
+        .collect();
+
+    for token in tokenize(schema) {
+        let start = byte_pos[token.get_index()];
+        match &token {
+            Token::Ident(i, name) if TYPE_NAMES.contains(&name.as_str()) => spans.push(Span {
+                start,
+                end: byte_pos[i + name.chars().count()],
+                style: TYPE,
+            }),
+            Token::Question(_) => spans.push(Span {
+                start,
+                end: start + 1,
+                style: NULLABLE,
+            }),
+            Token::Lt(_) => brackets.push(Bracket { pos: start, c: '<' }),
+            Token::Gt(_) => brackets.push(Bracket { pos: start, c: '>' }),
+            Token::LParen(_) => brackets.push(Bracket { pos: start, c: '(' }),
+            Token::RParen(_) => brackets.push(Bracket { pos: start, c: ')' }),
+            _ => {}
+        }
+    }
+}
+
+/// Finds the byte range of a flag's value in a command line, without the quotes around it
+fn find_flag_value(input: &str, flag: &str) -> Option<(usize, usize)> {
+    let flag_pos = input
+        .match_indices(flag)
+        .map(|(i, _)| i)
+        .find(|&i| i == 0 || input[..i].ends_with(char::is_whitespace))?;
+    let rest = &input[flag_pos + flag.len()..];
+    let value = rest.trim_start_matches(|c: char| c == '=' || c.is_whitespace());
+    let start = input.len() - value.len();
+
+    match value.chars().next() {
+        Some(quote @ ('\'' | '"')) => {
+            let content = &value[1..];
+            let len = content.find(quote).unwrap_or(content.len());
+            Some((start + 1, start + 1 + len))
+        }
+        Some(_) => {
+            let len = value.find(char::is_whitespace).unwrap_or(value.len());
+            Some((start, start + len))
+        }
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::repl::highlight::{check_brackets, highlight};
+
+    #[test]
+    fn test_check_brackets_sql() {
+        assert!(check_brackets("SELECT count(*) FROM t WHERE s = ')';").is_ok());
+        assert_eq!(
+            check_brackets("SELECT count(* FROM t;"),
+            Err("unclosed '('".to_string())
+        );
+        assert_eq!(
+            check_brackets("SELECT 1);"),
+            Err("unmatched ')'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_brackets_comments() {
+        assert!(check_brackets("SELECT 1 /* ( */;").is_ok());
+        assert!(check_brackets("SELECT 1 -- (\n;").is_ok());
+        assert_eq!(
+            check_brackets("SELECT count(* /* ) */ FROM t;"),
+            Err("unclosed '('".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_brackets_schema() {
+        assert!(check_brackets(".create --schema 'a: map<string, array<int>>' t ./t").is_ok());
+        assert_eq!(
+            check_brackets(".create --schema 'a: struct<b: array<int>' t ./t"),
+            Err("unclosed '<'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_highlight_keeps_text() {
+        let line = "select 'a(', 12 from t -- x";
+        let highlighted = highlight(line, Some(9));
+        let stripped: String = highlighted
+            .split('\x1b')
+            .enumerate()
+            .map(|(i, part)| {
+                if i == 0 {
+                    part
+                } else {
+                    &part[part.find('m').unwrap() + 1..]
+                }
+            })
+            .collect();
+        assert_eq!(stripped, line);
+    }
+}
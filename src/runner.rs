@@ -0,0 +1,299 @@
+use std::io::BufRead;
+
+use deltalake::datafusion::error::DataFusionError;
+use deltalake::datafusion::logical_expr::{LogicalPlan, WriteOp};
+
+use crate::commands::changes::changes_command;
+use crate::commands::checkout::checkout_command;
+use crate::commands::create_table::create_table_command;
+use crate::commands::diff::diff_command;
+use crate::commands::diff_files::diff_files_command;
+use crate::commands::display_schema::display_schema_command;
+use crate::commands::help::{help_command, unknown_command_message};
+use crate::commands::history::history_command;
+use crate::commands::insert::insert_command;
+use crate::commands::open_table::open_table_command;
+use crate::commands::profiles::profiles_command;
+use crate::commands::refresh::{auto_refresh, refresh_command};
+use crate::commands::restore::restore_command;
+use crate::commands::set::set_command;
+use crate::commands::tables::tables_command;
+use crate::commands::tail::tail_command;
+use crate::program_context::ProgramContext;
+use crate::query::delete::run_delete;
+use crate::query::insert::run_insert;
+use crate::query::merge::{is_merge, run_merge};
+use crate::query::time_travel::{deregister_snapshots, register_snapshots, rewrite_time_travel};
+use crate::query::update::run_update;
+
+/// A complete piece of input, ready to be run
+#[derive(Debug, PartialEq)]
+pub enum Statement {
+    Command(String),
+    Sql(String),
+    Quit,
+}
+
+/// Accumulates input lines into statements. Dot-commands take a single line,
+/// SQL keeps accumulating until a line ends with `;`.
+#[derive(Default)]
+pub struct StatementBuffer {
+    sql: String,
+}
+
+impl StatementBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// True when there is no partial SQL statement waiting for more lines
+    pub fn is_empty(&self) -> bool {
+        self.sql.is_empty()
+    }
+
+    /// The SQL lines entered so far for an unfinished statement
+    pub fn pending_sql(&self) -> &str {
+        &self.sql
+    }
+
+    pub fn push(&mut self, line: &str) -> Option<Statement> {
+        if matches!(line.trim(), "quit" | "exit" | "\\q") {
+            return Some(Statement::Quit);
+        }
+        if line.starts_with(".") {
+            return Some(Statement::Command(line.to_string()));
+        }
+
+        if self.sql.is_empty() {
+            self.sql = line.to_string();
+        } else {
+            self.sql = format!("{}\n{}", self.sql, line);
+        }
+        if self.sql.trim_end().ends_with(";") {
+            Some(Statement::Sql(std::mem::take(&mut self.sql)))
+        } else {
+            None
+        }
+    }
+
+    /// Returns whatever SQL is left without a terminating `;`
+    pub fn finish(&mut self) -> Option<Statement> {
+        let sql = std::mem::take(&mut self.sql);
+        if sql.trim().is_empty() {
+            None
+        } else {
+            Some(Statement::Sql(sql))
+        }
+    }
+}
+
+/// Runs every statement read from `reader`, stopping at the first one that fails unless
+/// `continue_on_error` is set, in which case the error is printed and the next one is run.
+/// Returns `Ok(true)` if a quit statement was found.
+pub async fn run_script(
+    ctx: &mut ProgramContext,
+    reader: impl BufRead,
+    continue_on_error: bool,
+) -> Result<bool, String> {
+    let mut sb = StatementBuffer::new();
+    for line in reader.lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        match sb.push(&line) {
+            Some(Statement::Quit) => return Ok(true),
+            Some(statement) => run_script_statement(ctx, statement, continue_on_error).await?,
+            None => {}
+        }
+    }
+    if let Some(statement) = sb.finish() {
+        run_script_statement(ctx, statement, continue_on_error).await?;
+    }
+    Ok(false)
+}
+
+async fn run_script_statement(
+    ctx: &mut ProgramContext,
+    statement: Statement,
+    continue_on_error: bool,
+) -> Result<(), String> {
+    match run_statement(ctx, statement).await {
+        Err(e) if continue_on_error => {
+            eprintln!("Error: {}", e);
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+pub async fn run_statement(ctx: &mut ProgramContext, statement: Statement) -> Result<(), String> {
+    match statement {
+        Statement::Command(line) => run_command(ctx, &line).await,
+        Statement::Sql(query) => run_query(ctx, &query).await,
+        Statement::Quit => Ok(()),
+    }
+}
+
+pub async fn run_query(ctx: &mut ProgramContext, query: &str) -> Result<(), String> {
+    if ctx.settings.auto_refresh {
+        auto_refresh(ctx).await?;
+    }
+    let rewrite = rewrite_time_travel(query)?;
+    let snapshots = register_snapshots(ctx, &rewrite.snapshots).await?;
+    let result = run_sql_or_write(ctx, &rewrite.sql).await;
+    deregister_snapshots(ctx, &snapshots);
+    result
+}
+
+/// Runs a query, sending the writes to opened Delta tables to delta-rs instead of DataFusion
+async fn run_sql_or_write(ctx: &mut ProgramContext, query: &str) -> Result<(), String> {
+    if is_merge(query) {
+        return run_merge(ctx, query).await;
+    }
+    let plan = ctx
+        .df_ctx
+        .state()
+        .create_logical_plan(query)
+        .await
+        .map_err(sql_error)?;
+    match plan {
+        LogicalPlan::Dml(dml) if ctx.tables.contains_key(dml.table_name.table()) => match dml.op {
+            WriteOp::Insert(_) => run_insert(ctx, dml).await,
+            WriteOp::Update => run_update(ctx, dml.table_name.table(), query).await,
+            WriteOp::Delete => run_delete(ctx, dml.table_name.table(), query).await,
+            _ => print_plan(ctx, LogicalPlan::Dml(dml)).await,
+        },
+        plan => print_plan(ctx, plan).await,
+    }
+}
+
+/// Runs a query in the DataFusion context and prints the result
+pub async fn run_sql(ctx: &ProgramContext, query: &str) -> Result<(), String> {
+    let plan = ctx
+        .df_ctx
+        .state()
+        .create_logical_plan(query)
+        .await
+        .map_err(sql_error)?;
+    print_plan(ctx, plan).await
+}
+
+async fn print_plan(ctx: &ProgramContext, plan: LogicalPlan) -> Result<(), String> {
+    let df = ctx
+        .df_ctx
+        .execute_logical_plan(plan)
+        .await
+        .map_err(sql_error)?;
+    println!("{}", df.to_string().await.map_err(|e| e.to_string())?);
+    Ok(())
+}
+
+/// SQL errors are shown without the DataFusion prefix
+fn sql_error(e: DataFusionError) -> String {
+    match e {
+        DataFusionError::SQL(e, _) => e.to_string(),
+        _ => e.to_string(),
+    }
+}
+
+pub async fn run_command(ctx: &mut ProgramContext, line: &str) -> Result<(), String> {
+    let args = shell_words::split(line).map_err(|e| e.to_string())?;
+
+    match args[0].as_str() {
+        ".help" => help_command(line),
+        ".open" => open_table_command(ctx, line).await,
+        ".create" => create_table_command(ctx, line).await,
+        ".tables" => tables_command(ctx, line),
+        ".schema" => display_schema_command(&ctx.df_ctx, line).await,
+        ".insert" => insert_command(ctx, line).await,
+        ".history" => history_command(ctx, line).await,
+        ".checkout" => checkout_command(ctx, line).await,
+        ".diff" => diff_command(ctx, line).await,
+        ".diff-files" => diff_files_command(ctx, line).await,
+        ".changes" => changes_command(ctx, line).await,
+        ".tail" => tail_command(ctx, line).await,
+        ".restore" => restore_command(ctx, line).await,
+        ".refresh" => refresh_command(ctx, line).await,
+        ".profiles" => profiles_command(line),
+        ".set" => set_command(ctx, line),
+        name => Err(unknown_command_message(name)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::{
+        program_context::ProgramContext,
+        runner::{Statement, StatementBuffer, run_script},
+        utils::test_tables::{create_table, query_rows},
+    };
+
+    #[test]
+    fn test_statement_buffer_multi_line() {
+        let mut sb = StatementBuffer::new();
+        assert_eq!(sb.push("SELECT *"), None);
+        assert_eq!(sb.push("FROM orders"), None);
+        assert_eq!(sb.pending_sql(), "SELECT *\nFROM orders");
+        assert_eq!(
+            sb.push("WHERE id = 1;  "),
+            Some(Statement::Sql(
+                "SELECT *\nFROM orders\nWHERE id = 1;  ".to_string()
+            ))
+        );
+        assert!(sb.is_empty());
+    }
+
+    #[test]
+    fn test_statement_buffer_semicolon() {
+        let mut sb = StatementBuffer::new();
+        assert_eq!(
+            sb.push("SELECT 1;"),
+            Some(Statement::Sql("SELECT 1;".to_string()))
+        );
+        assert_eq!(sb.push("SELECT 2"), None);
+        assert_eq!(sb.finish(), Some(Statement::Sql("SELECT 2".to_string())));
+        assert_eq!(sb.finish(), None);
+        assert_eq!(sb.push("   "), None);
+        assert_eq!(sb.finish(), None);
+    }
+
+    #[test]
+    fn test_statement_buffer_commands() {
+        let mut sb = StatementBuffer::new();
+        assert_eq!(sb.push("SELECT *"), None);
+        assert_eq!(
+            sb.push(".tables"),
+            Some(Statement::Command(".tables".to_string()))
+        );
+        assert_eq!(sb.pending_sql(), "SELECT *");
+        assert_eq!(
+            sb.push("FROM orders;"),
+            Some(Statement::Sql("SELECT *\nFROM orders;".to_string()))
+        );
+        assert_eq!(sb.push(" quit "), Some(Statement::Quit));
+        assert_eq!(sb.push("\\q"), Some(Statement::Quit));
+    }
+
+    #[tokio::test]
+    async fn test_run_script_continue_on_error() {
+        let mut ctx = ProgramContext::new();
+        let _dir = create_table(&mut ctx, "orders").await;
+        let script = "SELECT * FROM missing;\nINSERT INTO orders VALUES (1, 'pending');\n";
+
+        assert!(
+            run_script(&mut ctx, Cursor::new(script), false)
+                .await
+                .is_err()
+        );
+        assert_eq!(ctx.tables["orders"].version(), Some(0));
+
+        assert_eq!(
+            run_script(&mut ctx, Cursor::new(script), true).await,
+            Ok(false)
+        );
+        assert_eq!(
+            query_rows(&ctx, "SELECT * FROM orders").await,
+            vec!["1, pending"]
+        );
+    }
+}
@@ -29,6 +29,13 @@ impl Display for ParseError {
     }
 }
 
+/// Every type name accepted by the schema parser
+#[rustfmt::skip]
+pub const TYPE_NAMES: &[&str] = &[
+    "string", "long", "int", "short", "byte", "float", "double", "boolean", "binary", "date",
+    "timestamp", "array", "struct", "map",
+];
+
 pub fn parse_schema(schema_str: &str) -> Result<Vec<StructField>, ParseError> {
     let token_list = tokenize(schema_str);
     let mut i = 0;
@@ -0,0 +1,60 @@
+/// Session settings, changed with `.set`
+#[derive(Debug, Default, PartialEq)]
+pub struct Settings {
+    /// Check the opened tables for new commits before every query
+    pub auto_refresh: bool,
+    /// Run a DELETE without WHERE without asking for confirmation
+    pub force_delete: bool,
+}
+
+impl Settings {
+    pub const NAMES: &[&str] = &["auto_refresh", "force_delete"];
+
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "auto_refresh" => self.auto_refresh = parse_bool(value)?,
+            "force_delete" => self.force_delete = parse_bool(value)?,
+            _ => return Err(format!("unknown setting '{}'", name)),
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Result<String, String> {
+        match name {
+            "auto_refresh" => Ok(format_bool(self.auto_refresh)),
+            "force_delete" => Ok(format_bool(self.force_delete)),
+            _ => Err(format!("unknown setting '{}'", name)),
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "on" | "true" => Ok(true),
+        "off" | "false" => Ok(false),
+        _ => Err(format!("invalid value '{}', expected on or off", value)),
+    }
+}
+
+fn format_bool(value: bool) -> String {
+    if value { "on" } else { "off" }.to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::settings::Settings;
+
+    #[test]
+    fn test_set() {
+        let mut settings = Settings::default();
+        settings.set("auto_refresh", "ON").unwrap();
+        assert!(settings.auto_refresh);
+        assert_eq!(settings.get("auto_refresh"), Ok("on".to_string()));
+        settings.set("auto_refresh", "false").unwrap();
+        assert!(!settings.auto_refresh);
+        settings.set("force_delete", "on").unwrap();
+        assert!(settings.force_delete);
+        assert!(settings.set("auto_refresh", "maybe").is_err());
+        assert!(settings.set("auto_refersh", "on").is_err());
+    }
+}
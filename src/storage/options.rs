@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+use clap::Args;
+use serde::Deserialize;
+
+use super::profiles::load_profile;
+
+/// Storage options shared by `.open` and the `--open` startup flag.
+/// Also used as the contents of each profile in the profiles file.
+#[derive(Args, Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct StorageArgs {
+    /// Take the storage options from a profile in the profiles file
+    #[arg(long)]
+    #[serde(skip)]
+    pub profile: Option<String>,
+
+    /// http://host:port for MinIO / S3 endpoint
+    #[arg(long)]
+    pub endpoint_url: Option<String>,
+
+    /// Access key
+    #[arg(long)]
+    pub access_key_id: Option<String>,
+
+    /// Secret key
+    #[arg(long)]
+    pub secret_access_key: Option<String>,
+
+    /// Session token, for temporary credentials
+    #[arg(long)]
+    pub session_token: Option<String>,
+
+    /// Profile from ~/.aws/config and ~/.aws/credentials
+    #[arg(long)]
+    pub aws_profile: Option<String>,
+
+    /// AWS region
+    #[arg(long)]
+    pub region: Option<String>,
+
+    /// true/false [default: true for http:// endpoints]
+    #[arg(long, action = clap::ArgAction::Set)]
+    pub allow_http: Option<bool>,
+
+    /// S3 addressing style: "path" or "virtual"
+    #[arg(long, value_parser = ["path", "virtual"])]
+    pub addressing_style: Option<String>,
+
+    /// Conditional write mechanism: "etag" (MinIO) or "dynamodb" (AWS)
+    #[arg(long, value_parser = ["etag", "dynamodb"])]
+    pub conditional_put: Option<String>,
+}
+
+impl StorageArgs {
+    /// Fills the options that were not given explicitly from the selected profile, if any
+    pub fn resolve(&self) -> Result<StorageArgs, String> {
+        match &self.profile {
+            Some(name) => Ok(self.clone().or(load_profile(name)?)),
+            None => Ok(self.clone()),
+        }
+    }
+
+    /// Keeps the options set in `self`, taking the rest from `fallback`
+    pub fn or(self, fallback: StorageArgs) -> StorageArgs {
+        StorageArgs {
+            profile: self.profile.or(fallback.profile),
+            endpoint_url: self.endpoint_url.or(fallback.endpoint_url),
+            access_key_id: self.access_key_id.or(fallback.access_key_id),
+            secret_access_key: self.secret_access_key.or(fallback.secret_access_key),
+            session_token: self.session_token.or(fallback.session_token),
+            aws_profile: self.aws_profile.or(fallback.aws_profile),
+            region: self.region.or(fallback.region),
+            allow_http: self.allow_http.or(fallback.allow_http),
+            addressing_style: self.addressing_style.or(fallback.addressing_style),
+            conditional_put: self.conditional_put.or(fallback.conditional_put),
+        }
+    }
+
+    /// Only the options that were set are passed on. Everything else is left to the
+    /// standard AWS credential chain: environment variables, AWS_PROFILE, ~/.aws/config
+    /// and ~/.aws/credentials.
+    pub fn storage_options(&self) -> HashMap<String, String> {
+        let mut storage_options: HashMap<String, String> = HashMap::new();
+
+        if let Some(v) = &self.endpoint_url {
+            storage_options.insert("AWS_ENDPOINT_URL".into(), v.clone());
+        }
+        if let Some(v) = &self.access_key_id {
+            storage_options.insert("AWS_ACCESS_KEY_ID".into(), v.clone());
+        }
+        if let Some(v) = &self.secret_access_key {
+            storage_options.insert("AWS_SECRET_ACCESS_KEY".into(), v.clone());
+        }
+        if let Some(v) = &self.session_token {
+            storage_options.insert("AWS_SESSION_TOKEN".into(), v.clone());
+        }
+        if let Some(v) = &self.aws_profile {
+            storage_options.insert("AWS_PROFILE".into(), v.clone());
+        }
+        if let Some(v) = &self.region {
+            storage_options.insert("AWS_REGION".into(), v.clone());
+        }
+
+        let http_endpoint = self
+            .endpoint_url
+            .as_ref()
+            .is_some_and(|url| url.starts_with("http://"));
+        if let Some(v) = self.allow_http.or(http_endpoint.then_some(true)) {
+            storage_options.insert("AWS_ALLOW_HTTP".into(), v.to_string());
+        }
+        if let Some(v) = &self.addressing_style {
+            storage_options.insert("AWS_S3_ADDRESSING_STYLE".into(), v.clone());
+        }
+        if let Some(v) = &self.conditional_put {
+            storage_options.insert("aws_conditional_put".into(), v.clone());
+        }
+
+        storage_options
+    }
+
+    /// The options that are set, as (flag name, value) pairs, with secrets masked
+    pub fn masked_entries(&self) -> Vec<(&'static str, String)> {
+        let mut entries = Vec::new();
+        if let Some(v) = &self.endpoint_url {
+            entries.push(("endpoint-url", v.clone()));
+        }
+        if let Some(v) = &self.access_key_id {
+            entries.push(("access-key-id", mask(v, 4)));
+        }
+        if let Some(v) = &self.secret_access_key {
+            entries.push(("secret-access-key", mask(v, 0)));
+        }
+        if let Some(v) = &self.session_token {
+            entries.push(("session-token", mask(v, 0)));
+        }
+        if let Some(v) = &self.aws_profile {
+            entries.push(("aws-profile", v.clone()));
+        }
+        if let Some(v) = &self.region {
+            entries.push(("region", v.clone()));
+        }
+        if let Some(v) = self.allow_http {
+            entries.push(("allow-http", v.to_string()));
+        }
+        if let Some(v) = &self.addressing_style {
+            entries.push(("addressing-style", v.clone()));
+        }
+        if let Some(v) = &self.conditional_put {
+            entries.push(("conditional-put", v.clone()));
+        }
+        entries
+    }
+}
+
+/// Keeps the first `visible` characters of a secret, and hides the rest
+fn mask(secret: &str, visible: usize) -> String {
+    let shown: String = secret.chars().take(visible).collect();
+    format!("{}****", shown)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::storage::options::StorageArgs;
+
+    #[test]
+    fn test_or_prefers_explicit_options() {
+        let flags = StorageArgs {
+            profile: Some("minio-dev".to_string()),
+            endpoint_url: Some("http://minio:9000".to_string()),
+            ..Default::default()
+        };
+        let profile = StorageArgs {
+            endpoint_url: Some("http://localhost:9000".to_string()),
+            access_key_id: Some("minio".to_string()),
+            allow_http: Some(false),
+            ..Default::default()
+        };
+
+        let args = flags.or(profile);
+        assert_eq!(args.profile.as_deref(), Some("minio-dev"));
+        assert_eq!(args.endpoint_url.as_deref(), Some("http://minio:9000"));
+        assert_eq!(args.access_key_id.as_deref(), Some("minio"));
+        assert_eq!(args.allow_http, Some(false));
+        assert_eq!(args.region, None);
+    }
+
+    #[test]
+    fn test_storage_options_allow_http() {
+        let http = StorageArgs {
+            endpoint_url: Some("http://localhost:9000".to_string()),
+            ..Default::default()
+        };
+        let options = http.storage_options();
+        assert_eq!(options["AWS_ENDPOINT_URL"], "http://localhost:9000");
+        assert_eq!(options["AWS_ALLOW_HTTP"], "true");
+
+        let https = StorageArgs {
+            endpoint_url: Some("https://s3.example.com".to_string()),
+            ..Default::default()
+        };
+        assert!(!https.storage_options().contains_key("AWS_ALLOW_HTTP"));
+
+        let explicit = StorageArgs {
+            allow_http: Some(false),
+            ..http
+        };
+        assert_eq!(explicit.storage_options()["AWS_ALLOW_HTTP"], "false");
+
+        assert!(StorageArgs::default().storage_options().is_empty());
+    }
+
+    #[test]
+    fn test_masked_entries() {
+        let args = StorageArgs {
+            region: Some("eu-west-1".to_string()),
+            access_key_id: Some("AKIAEXAMPLE".to_string()),
+            secret_access_key: Some("secret".to_string()),
+            session_token: Some("token".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            args.masked_entries(),
+            vec![
+                ("access-key-id", "AKIA****".to_string()),
+                ("secret-access-key", "****".to_string()),
+                ("session-token", "****".to_string()),
+                ("region", "eu-west-1".to_string()),
+            ]
+        );
+    }
+}
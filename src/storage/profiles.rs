@@ -0,0 +1,124 @@
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+use super::options::StorageArgs;
+
+/// Location of the profiles file. Can be changed with the DELTAQ_PROFILES environment variable
+pub fn profiles_path() -> Option<PathBuf> {
+    select_profiles_path(std::env::var_os("DELTAQ_PROFILES"), dirs::config_dir())
+}
+
+fn select_profiles_path(
+    env_path: Option<OsString>,
+    config_dir: Option<PathBuf>,
+) -> Option<PathBuf> {
+    if let Some(path) = env_path {
+        return Some(PathBuf::from(path));
+    }
+    config_dir.map(|dir| dir.join("deltaq").join("profiles.toml"))
+}
+
+/// Reads every profile in the profiles file. A missing file means there are no profiles
+pub fn load_profiles() -> Result<BTreeMap<String, StorageArgs>, String> {
+    match profiles_path() {
+        Some(path) => read_profiles(&path),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+fn read_profiles(path: &Path) -> Result<BTreeMap<String, StorageArgs>, String> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let content =
+        std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    parse_profiles(&content).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn parse_profiles(content: &str) -> Result<BTreeMap<String, StorageArgs>, String> {
+    toml::from_str(content).map_err(|e| e.to_string())
+}
+
+pub fn load_profile(name: &str) -> Result<StorageArgs, String> {
+    load_profiles()?
+        .remove(name)
+        .ok_or_else(|| format!("profile '{}' not found", name))
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use crate::storage::profiles::{parse_profiles, read_profiles, select_profiles_path};
+
+    const PROFILES: &str = r#"
+[minio-dev]
+endpoint-url = "http://localhost:9000"
+access-key-id = "minio"
+secret-access-key = "minio12345"
+addressing-style = "path"
+
+[prod]
+region = "eu-west-1"
+allow-http = false
+"#;
+
+    #[test]
+    fn test_parse_profiles() {
+        let profiles = parse_profiles(PROFILES).unwrap();
+        assert_eq!(
+            profiles.keys().collect::<Vec<_>>(),
+            vec!["minio-dev", "prod"]
+        );
+
+        let minio = &profiles["minio-dev"];
+        assert_eq!(minio.endpoint_url.as_deref(), Some("http://localhost:9000"));
+        assert_eq!(minio.access_key_id.as_deref(), Some("minio"));
+        assert_eq!(minio.addressing_style.as_deref(), Some("path"));
+        assert_eq!(minio.region, None);
+
+        let prod = &profiles["prod"];
+        assert_eq!(prod.region.as_deref(), Some("eu-west-1"));
+        assert_eq!(prod.allow_http, Some(false));
+    }
+
+    #[test]
+    fn test_parse_profiles_unknown_field() {
+        let err = parse_profiles("[dev]\nendpoint = \"http://localhost:9000\"\n").unwrap_err();
+        assert!(err.contains("unknown field `endpoint`"), "{}", err);
+    }
+
+    #[test]
+    fn test_profiles_path() {
+        let config_dir = Some(PathBuf::from("/home/me/.config"));
+        assert_eq!(
+            select_profiles_path(None, config_dir.clone()),
+            Some(PathBuf::from("/home/me/.config/deltaq/profiles.toml"))
+        );
+        assert_eq!(
+            select_profiles_path(Some("/etc/deltaq.toml".into()), config_dir),
+            Some(PathBuf::from("/etc/deltaq.toml"))
+        );
+        assert_eq!(select_profiles_path(None, None), None);
+    }
+
+    #[test]
+    fn test_read_profiles_from_config_dir() {
+        let config_dir = tempfile::tempdir().unwrap();
+        let path = select_profiles_path(None, Some(config_dir.path().to_path_buf())).unwrap();
+        assert!(read_profiles(&path).unwrap().is_empty());
+
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, PROFILES).unwrap();
+        let profiles = read_profiles(&path).unwrap();
+        assert_eq!(profiles["prod"].region.as_deref(), Some("eu-west-1"));
+
+        std::fs::write(&path, "[dev]\nregion = 1\n").unwrap();
+        let err = read_profiles(&path).unwrap_err();
+        assert!(err.starts_with(&path.display().to_string()), "{}", err);
+    }
+}
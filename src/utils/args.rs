@@ -0,0 +1,16 @@
+use clap::Parser;
+
+/// Parses a command line into its clap arguments struct.
+///
+/// Returns `Ok(None)` when clap already printed the help, so there is nothing left to run.
+pub fn parse_args<T: Parser>(line: &str) -> Result<Option<T>, String> {
+    let words = shell_words::split(line).map_err(|e| e.to_string())?;
+    match T::try_parse_from(words) {
+        Ok(args) => Ok(Some(args)),
+        Err(e) if !e.use_stderr() => {
+            e.print().expect("error writing to stdout");
+            Ok(None)
+        }
+        Err(e) => Err(e.to_string().trim_end().to_string()),
+    }
+}
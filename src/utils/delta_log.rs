@@ -0,0 +1,36 @@
+use deltalake::{DeltaTable, Path, kernel::Action};
+
+/// Reads the actions of a single commit from the log of a table
+pub async fn read_commit(table: &DeltaTable, version: i64) -> Result<Vec<Action>, String> {
+    read_commit_entry(table, version)
+        .await?
+        .ok_or_else(|| format!("version {} not found in the log", version))
+}
+
+/// Like `read_commit`, but `None` when the commit is no longer in the log
+pub async fn read_commit_entry(
+    table: &DeltaTable,
+    version: i64,
+) -> Result<Option<Vec<Action>>, String> {
+    let Some(bytes) = table
+        .log_store()
+        .read_commit_entry(version)
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(None);
+    };
+
+    // One JSON action per line
+    bytes
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.trim_ascii().is_empty())
+        .map(|line| serde_json::from_slice(line).map_err(|e| e.to_string()))
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+/// Paths in the log are URL encoded, while the ones in a loaded snapshot are not
+pub fn decode_path(path: &str) -> String {
+    Path::from_url_path(path).map_or(path.to_string(), |p| p.to_string())
+}
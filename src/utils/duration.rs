@@ -0,0 +1,46 @@
+use std::time::Duration;
+
+/// Parses a duration like `500ms`, `5s`, `2m` or `1h`. A plain number is taken as seconds
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let invalid = || {
+        format!(
+            "invalid duration '{}', expected e.g. 500ms, 5s, 2m or 1h",
+            s
+        )
+    };
+
+    let number: u64 = number.parse().map_err(|_| invalid())?;
+    let duration = match unit {
+        "ms" => Duration::from_millis(number),
+        "" | "s" => Duration::from_secs(number),
+        "m" => Duration::from_secs(number * 60),
+        "h" => Duration::from_secs(number * 60 * 60),
+        _ => return Err(invalid()),
+    };
+    if duration.is_zero() {
+        return Err(format!("duration '{}' must be greater than zero", s));
+    }
+    Ok(duration)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::utils::duration::parse_duration;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("5s"), Ok(Duration::from_secs(5)));
+        assert_eq!(parse_duration("10"), Ok(Duration::from_secs(10)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("s").is_err());
+        assert!(parse_duration("5 days").is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+
+/// Flags whose values must never be written to the history file
+const SECRET_FLAGS: [&str; 3] = ["--access-key-id", "--secret-access-key", "--session-token"];
+
+const REDACTED: &str = "REDACTED";
+
+/// Location of the REPL history file, under the user's data dir
+pub fn history_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("deltaq").join("history"))
+}
+
+/// Creates the history file and its directory if they don't exist. The file can only be read
+/// by the user, as it has every query typed in the REPL
+pub fn create_history_file(path: &Path) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut options = OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+        options.mode(0o600);
+        let file = options.open(path)?;
+        // The mode only applies to new files
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    #[cfg(not(unix))]
+    options.open(path)?;
+    Ok(())
+}
+
+/// Replaces the values of credential flags in a command line, so it can be stored in the history
+pub fn redact_secrets(line: &str) -> String {
+    if !SECRET_FLAGS.iter().any(|flag| line.contains(flag)) {
+        return line.to_string();
+    }
+
+    let Ok(words) = shell_words::split(line) else {
+        // Without the words we can't tell where the secret ends, so only the command is kept
+        return line
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+    };
+
+    let mut redacted = Vec::with_capacity(words.len());
+    let mut redact_next = false;
+    for word in words {
+        if redact_next {
+            redacted.push(REDACTED.to_string());
+            redact_next = false;
+            continue;
+        }
+        match SECRET_FLAGS.iter().find(|flag| word.starts_with(*flag)) {
+            Some(flag) if word == *flag => {
+                redacted.push(word);
+                redact_next = true;
+            }
+            // --flag=value is stored as --flag REDACTED
+            Some(flag) if word[flag.len()..].starts_with('=') => {
+                redacted.push(flag.to_string());
+                redacted.push(REDACTED.to_string());
+            }
+            _ => redacted.push(word),
+        }
+    }
+
+    shell_words::join(redacted)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::history::{create_history_file, redact_secrets};
+
+    #[test]
+    fn test_redact_secrets() {
+        assert_eq!(
+            redact_secrets(".open t s3://b/t --access-key-id minio --secret-access-key 'a b'"),
+            ".open t s3://b/t --access-key-id REDACTED --secret-access-key REDACTED"
+        );
+        assert_eq!(
+            redact_secrets(".open t s3://b/t --secret-access-key=abc --region eu-west-1"),
+            ".open t s3://b/t --secret-access-key REDACTED --region eu-west-1"
+        );
+    }
+
+    #[test]
+    fn test_redact_secrets_untouched() {
+        let line = ".open t s3://b/t   --profile 'minio dev'";
+        assert_eq!(redact_secrets(line), line);
+    }
+
+    #[test]
+    fn test_redact_secrets_unbalanced_quotes() {
+        assert_eq!(
+            redact_secrets(".open t s3://b/t --secret-access-key 'abc"),
+            ".open"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_history_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("deltaq").join("history");
+        create_history_file(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        std::fs::write(&path, "SELECT 1;\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        create_history_file(&path).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "SELECT 1;\n");
+    }
+}
@@ -0,0 +1,92 @@
+use std::ops::ControlFlow;
+
+use deltalake::datafusion::sql::sqlparser::{
+    ast::{
+        AssignmentTarget, Expr, Statement, TableFactor, visit_expressions, visit_expressions_mut,
+    },
+    dialect::GenericDialect,
+    parser::Parser,
+};
+
+/// Quotes an identifier for DataFusion SQL, keeping its case
+pub fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// Quotes a string literal for DataFusion SQL
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Parses a query that must hold a single statement
+pub fn parse_statement(sql: &str) -> Result<Statement, String> {
+    let mut statements = Parser::parse_sql(&GenericDialect {}, sql).map_err(|e| e.to_string())?;
+    match statements.len() {
+        1 => Ok(statements.remove(0)),
+        n => Err(format!("expected a single statement, found {}", n)),
+    }
+}
+
+/// The names columns of the table can be qualified with in a statement: the table name
+/// and its alias, as written
+pub fn table_qualifiers(table: &TableFactor) -> Vec<String> {
+    let mut qualifiers = Vec::new();
+    if let TableFactor::Table { name, alias, .. } = table {
+        qualifiers.push(name.to_string());
+        if let Some(alias) = alias {
+            qualifiers.push(alias.name.to_string());
+        }
+    }
+    qualifiers
+}
+
+/// Turns an expression of an `UPDATE` or `DELETE` into SQL for delta-rs, which parses it again
+/// against the table schema alone. The columns lose their qualifier, and subqueries are rejected,
+/// as delta-rs can't run them
+pub fn table_expr(
+    mut expr: Expr,
+    qualifiers: &[String],
+    statement: &str,
+) -> Result<String, String> {
+    let subquery = visit_expressions(&expr, |e| match e {
+        Expr::Subquery(_) | Expr::InSubquery { .. } | Expr::Exists { .. } => ControlFlow::Break(()),
+        _ => ControlFlow::Continue(()),
+    });
+    if subquery.is_break() {
+        return Err(format!(
+            "subqueries are not supported in {} on Delta tables, use MERGE INTO",
+            statement
+        ));
+    }
+
+    let _ = visit_expressions_mut(&mut expr, |e| {
+        let column = match e {
+            Expr::CompoundIdentifier(parts)
+                if parts.len() == 2 && qualifiers.contains(&parts[0].to_string()) =>
+            {
+                Some(parts[1].clone())
+            }
+            _ => None,
+        };
+        if let Some(column) = column {
+            *e = Expr::Identifier(column);
+        }
+        ControlFlow::<()>::Continue(())
+    });
+    Ok(expr.to_string())
+}
+
+/// The column set by an assignment, without its qualifier
+pub fn assignment_column(
+    target: &AssignmentTarget,
+    qualifiers: &[String],
+) -> Result<String, String> {
+    let AssignmentTarget::ColumnName(column) = target else {
+        return Err("only single column assignments are supported".to_string());
+    };
+    let column = column.to_string();
+    let unqualified = qualifiers
+        .iter()
+        .find_map(|q| column.strip_prefix(&format!("{}.", q)));
+    Ok(unqualified.unwrap_or(&column).to_string())
+}
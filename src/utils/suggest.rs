@@ -0,0 +1,39 @@
+/// Finds the candidate closest to `input`, if any is close enough to be a likely typo
+pub fn did_you_mean<'a>(input: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|c| (edit_distance(input, c), *c))
+        .filter(|(distance, c)| *distance <= 2.max(c.len() / 3))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, c)| c)
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use crate::utils::suggest::did_you_mean;
+
+    #[test]
+    fn test_did_you_mean() {
+        let commands = [".open", ".create", ".tables", ".schema"];
+        assert_eq!(did_you_mean(".tabels", &commands), Some(".tables"));
+        assert_eq!(did_you_mean(".opne", &commands), Some(".open"));
+        assert_eq!(did_you_mean(".vacuum", &commands), None);
+    }
+}
@@ -0,0 +1,51 @@
+use deltalake::{
+    DeltaOps,
+    arrow::util::display::{ArrayFormatter, FormatOptions},
+    datafusion::dataframe::DataFrame,
+};
+use tempfile::TempDir;
+
+use crate::{program_context::ProgramContext, schema::parser::parse_schema};
+
+/// Creates an empty `id: long, status: string?` table in a temporary directory, and opens it
+/// as `table_name`. The table is deleted when the returned directory is dropped
+pub async fn create_table(ctx: &mut ProgramContext, table_name: &str) -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let table = DeltaOps::try_from_uri(dir.path().to_str().unwrap())
+        .await
+        .unwrap()
+        .create()
+        .with_table_name(table_name)
+        .with_columns(parse_schema("id: long, status: string?").unwrap())
+        .await
+        .unwrap();
+    ctx.refresh_table(table_name, table).await;
+    dir
+}
+
+/// Every row of a query, with its values separated by commas, sorted
+pub async fn query_rows(ctx: &ProgramContext, sql: &str) -> Vec<String> {
+    rows(ctx.df_ctx.sql(sql).await.unwrap()).await
+}
+
+/// Every row of a DataFrame, with its values separated by commas, sorted
+pub async fn rows(df: DataFrame) -> Vec<String> {
+    let options = FormatOptions::default().with_null("NULL");
+    let mut rows = Vec::new();
+    for batch in df.collect().await.unwrap() {
+        let formatters: Vec<ArrayFormatter> = batch
+            .columns()
+            .iter()
+            .map(|column| ArrayFormatter::try_new(column.as_ref(), &options).unwrap())
+            .collect();
+        for row in 0..batch.num_rows() {
+            let values: Vec<String> = formatters
+                .iter()
+                .map(|f| f.value(row).to_string())
+                .collect();
+            rows.push(values.join(", "));
+        }
+    }
+    rows.sort();
+    rows
+}
@@ -0,0 +1,85 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use deltalake::{DeltaTable, DeltaTableError};
+
+/// A point in the history of a table
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableVersion {
+    Version(i64),
+    Timestamp(DateTime<Utc>),
+    Latest,
+}
+
+impl TableVersion {
+    /// Parses "latest", a version number, or a timestamp
+    pub fn parse(s: &str) -> Result<TableVersion, String> {
+        if s.eq_ignore_ascii_case("latest") {
+            Ok(TableVersion::Latest)
+        } else if let Ok(version) = s.parse::<i64>() {
+            Ok(TableVersion::Version(version))
+        } else {
+            parse_timestamp(s).map(TableVersion::Timestamp)
+        }
+    }
+
+    /// Loads this version into the table
+    pub async fn load(&self, table: &mut DeltaTable) -> Result<(), DeltaTableError> {
+        match self {
+            TableVersion::Version(version) => table.load_version(*version).await,
+            TableVersion::Timestamp(timestamp) => table.load_with_datetime(*timestamp).await,
+            TableVersion::Latest => table.update_incremental(None).await,
+        }
+    }
+}
+
+impl Display for TableVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableVersion::Version(version) => write!(f, "version {}", version),
+            TableVersion::Timestamp(timestamp) => write!(f, "{}", timestamp.to_rfc3339()),
+            TableVersion::Latest => write!(f, "latest"),
+        }
+    }
+}
+
+/// The version of a loaded table, for messages
+pub fn format_version(version: Option<i64>) -> String {
+    version.map_or("no version".to_string(), |v| format!("version {}", v))
+}
+
+/// Parses an RFC 3339 timestamp, or a date, taken as midnight UTC
+pub fn parse_timestamp(s: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(s) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap().and_utc())
+        .map_err(|_| format!("invalid timestamp '{}', expected RFC 3339 or YYYY-MM-DD", s))
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{TimeZone, Utc};
+
+    use crate::utils::time_travel::TableVersion;
+
+    #[test]
+    fn test_parse_table_version() {
+        assert_eq!(TableVersion::parse("latest"), Ok(TableVersion::Latest));
+        assert_eq!(TableVersion::parse("12"), Ok(TableVersion::Version(12)));
+        assert_eq!(
+            TableVersion::parse("2026-01-01T10:00:00+02:00"),
+            Ok(TableVersion::Timestamp(
+                Utc.with_ymd_and_hms(2026, 1, 1, 8, 0, 0).unwrap()
+            ))
+        );
+        assert_eq!(
+            TableVersion::parse("2026-01-01"),
+            Ok(TableVersion::Timestamp(
+                Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()
+            ))
+        );
+        assert!(TableVersion::parse("yesterday").is_err());
+    }
+}